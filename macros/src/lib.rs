@@ -0,0 +1,198 @@
+//! `local_mcp_server` 的过程宏：由带类型参数的函数定义 MCP 工具
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{FnArg, ItemFn, LitStr, Type, meta::ParseNestedMeta, parse_macro_input};
+
+/// `#[mcp_tool(...)]` 的参数
+#[derive(Default)]
+struct ToolAttrs {
+    name: Option<LitStr>,
+    title: Option<LitStr>,
+    description: Option<LitStr>,
+    /// 工具注解，如 readOnlyHint
+    hints: Vec<&'static str>,
+    /// 结果随时间或随机变化，不允许缓存
+    non_deterministic: bool,
+    /// 值为文件路径的参数
+    path_arguments: Vec<LitStr>,
+}
+
+impl ToolAttrs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("title") {
+            self.title = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("description") {
+            self.description = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("read_only") {
+            self.hints.push("readOnlyHint");
+        } else if meta.path.is_ident("destructive") {
+            self.hints.push("destructiveHint");
+        } else if meta.path.is_ident("idempotent") {
+            self.hints.push("idempotentHint");
+        } else if meta.path.is_ident("open_world") {
+            self.hints.push("openWorldHint");
+        } else if meta.path.is_ident("non_deterministic") {
+            self.non_deterministic = true;
+        } else if meta.path.is_ident("path_argument") {
+            self.path_arguments.push(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("未知的 mcp_tool 参数"));
+        }
+        Ok(())
+    }
+}
+
+/// 将函数定义为 MCP 工具，并生成返回注册表条目的 `<函数名>_entry()` 函数
+///
+/// 函数最多接收一个工具参数，参数类型需实现 `Deserialize` 与 `JsonSchema`，输入 schema
+/// 由该类型生成；另外可以接收一个 `ToolContext` 获取会话状态、取消信号与进度上报。
+/// 返回值为 `ToolCallResult` 或 `Result<ToolCallResult, ToolError>`。
+///
+/// ```ignore
+/// #[mcp_tool(name = "random string", description = "生成随机字符串", read_only)]
+/// pub async fn random_string(params: RandomStringParams) -> ToolCallResult { ... }
+///
+/// let entry = random_string_entry();
+/// ```
+///
+/// 可用参数：`name`（默认为函数名）、`title`、`description`（必填），注解
+/// `read_only`、`destructive`、`idempotent`、`open_world`，`non_deterministic`
+/// （结果随时间或随机变化，不允许配置结果缓存），以及可重复的 `path_argument = "参数名"`
+/// （值为文件路径的参数，审批规则按解析后的真实路径匹配）。
+#[proc_macro_attribute]
+pub fn mcp_tool(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut attrs = ToolAttrs::default();
+    let parser = syn::meta::parser(|meta| attrs.parse(meta));
+    parse_macro_input!(attr with parser);
+    let func = parse_macro_input!(item as ItemFn);
+
+    match expand(attrs, func) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(attrs: ToolAttrs, func: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &func.sig;
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "mcp_tool 不支持泛型函数",
+        ));
+    }
+    let Some(description) = attrs.description else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "mcp_tool 需要 description 参数",
+        ));
+    };
+
+    let ident = &sig.ident;
+    let vis = &func.vis;
+    let entry_ident = format_ident!("{}_entry", ident);
+    let name = attrs
+        .name
+        .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+    let title = match attrs.title {
+        Some(title) => quote!(::std::option::Option::Some(#title.to_string())),
+        None => quote!(::std::option::Option::None),
+    };
+    let hints = attrs.hints;
+    let non_deterministic = attrs
+        .non_deterministic
+        .then(|| quote!(.non_deterministic()));
+    let path_arguments = attrs.path_arguments;
+    let await_call = sig.asyncness.map(|_| quote!(.await));
+
+    let registry = quote!(::local_mcp_server::tools::registry);
+    let dto = quote!(::local_mcp_server::tools::tool_dto);
+
+    // 参数分为调用上下文（ToolContext）与工具参数，各最多一个，顺序不限
+    let mut args_type = None;
+    let mut call_args = Vec::new();
+    let mut has_context = false;
+    for input in &sig.inputs {
+        let FnArg::Typed(arg) = input else {
+            return Err(syn::Error::new_spanned(input, "mcp_tool 不能用于方法"));
+        };
+        if is_tool_context(&arg.ty) {
+            if has_context {
+                return Err(syn::Error::new_spanned(input, "ToolContext 参数重复"));
+            }
+            has_context = true;
+            call_args.push(quote!(ctx));
+        } else {
+            if args_type.is_some() {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "mcp_tool 函数除 ToolContext 外最多只能有一个参数",
+                ));
+            }
+            args_type = Some(&arg.ty);
+            call_args.push(quote!(params));
+        }
+    }
+
+    let (input_schema, parse) = match args_type {
+        Some(ty) => (
+            quote!(#registry::input_schema_for::<#ty>()),
+            quote! {
+                let params = match #registry::parse_arguments::<#ty>(args) {
+                    ::std::result::Result::Ok(params) => params,
+                    ::std::result::Result::Err(error) => {
+                        return #dto::ToolCallResult::from(error);
+                    }
+                };
+            },
+        ),
+        None => (
+            quote!(#registry::input_schema_for::<()>()),
+            quote!(let _ = args;),
+        ),
+    };
+    let ignore_context = (!has_context).then(|| quote!(let _ = ctx;));
+    let call = quote! {
+        #ignore_context
+        #parse
+        #dto::ToolCallResult::from(#ident(#(#call_args),*) #await_call)
+    };
+
+    let entry_doc = format!("`{}` 工具的注册表条目，由 `#[mcp_tool]` 生成", name.value());
+
+    Ok(quote! {
+        #func
+
+        #[doc = #entry_doc]
+        #vis fn #entry_ident() -> #registry::ToolEntry {
+            #registry::ToolEntry::with_context(
+                #dto::Tool {
+                    name: #name.to_string(),
+                    title: #title,
+                    description: #description.to_string(),
+                    input_schema: #input_schema,
+                    output_schema: ::std::option::Option::None,
+                    annotations: #registry::tool_annotations(&[#(#hints),*]),
+                },
+                |ctx, args| async move { #call },
+            )
+            #non_deterministic
+            #(.path_argument(#path_arguments))*
+        }
+    })
+}
+
+/// 参数类型是否为 ToolContext（按类型名最后一段判断）
+fn is_tool_context(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "ToolContext"),
+        _ => false,
+    }
+}
@@ -1,6 +1,378 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr},
+    sync::Arc,
+    time::Duration,
+};
+
 use serde::Deserialize;
 
+use crate::server::state;
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
-    pub listen_port: u16,
+    /// TCP监听端口，不配置时不监听TCP
+    pub listen_port: Option<u16>,
+    /// TCP监听地址，支持IPv4与IPv6，默认仅监听 127.0.0.1
+    #[serde(default = "default_bind_addresses")]
+    pub bind_addresses: Vec<IpAddr>,
+    /// Unix域套接字监听配置
+    pub unix_socket: Option<UnixSocketConfig>,
+    /// 认证配置，不配置时不做认证
+    pub auth: Option<AuthConfig>,
+    /// 允许的浏览器来源（Origin），支持 "http://localhost:*" 形式的端口通配；
+    /// 不配置时只允许本机回环地址
+    pub allowed_origins: Option<Vec<String>>,
+    /// TLS配置，配置后 listen_port 提供 HTTPS 服务
+    pub tls: Option<TlsConfig>,
+    /// 检查配置文件变化的间隔（秒），为 0 时只在收到 SIGHUP 时重新加载
+    #[serde(default = "default_config_watch_interval")]
+    pub config_watch_interval_secs: u64,
+    /// 按工具名配置启用状态与参数默认值，未配置的工具默认启用
+    #[serde(default)]
+    pub tools: HashMap<String, ToolConfig>,
+    /// 默认调用频率限制，对未单独配置的工具生效
+    pub rate_limit: Option<RateLimitConfig>,
+    /// 新建会话的频率限制，按认证身份或客户端IP计数；设为 null 时不限制
+    #[serde(default = "default_session_rate_limit")]
+    pub session_rate_limit: Option<RateLimitConfig>,
+    /// 工具调用的默认超时（毫秒），包含等待并发许可的时间
+    #[serde(default = "default_tool_timeout")]
+    pub tool_timeout_ms: u64,
+    /// 工具调用审计日志，不配置时不记录
+    pub audit: Option<AuditConfig>,
+    /// 运行日志与链路追踪配置
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// 收到 SIGINT/SIGTERM 后等待进行中的调用完成的最长时间（秒）
+    #[serde(default = "default_shutdown_drain")]
+    pub shutdown_drain_secs: u64,
+    /// 人工审批配置，对设置了 require_approval 的工具生效
+    #[serde(default)]
+    pub approval: ApprovalConfig,
+    /// 文件工具可以访问的路径
+    #[serde(default)]
+    pub path_policy: PathPolicyConfig,
+}
+
+fn default_shutdown_drain() -> u64 {
+    30
+}
+
+fn default_session_rate_limit() -> Option<RateLimitConfig> {
+    Some(RateLimitConfig {
+        per_minute: 60,
+        burst: None,
+    })
+}
+
+fn default_tool_timeout() -> u64 {
+    30_000
+}
+
+fn default_config_watch_interval() -> u64 {
+    2
+}
+
+fn default_bind_addresses() -> Vec<IpAddr> {
+    vec![IpAddr::V4(Ipv4Addr::LOCALHOST)]
+}
+
+/// Unix域套接字配置
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct UnixSocketConfig {
+    /// 套接字文件路径
+    pub path: String,
+    /// 套接字文件权限（八进制，如 "600"），默认仅当前用户可读写
+    #[serde(default = "default_socket_mode")]
+    pub mode: String,
+}
+
+fn default_socket_mode() -> String {
+    "600".to_string()
+}
+
+impl UnixSocketConfig {
+    /// 解析八进制权限字符串，支持 "600"、"0600"、"0o600"
+    pub fn permissions_mode(&self) -> Result<u32, String> {
+        let digits = self.mode.trim().trim_start_matches("0o");
+        u32::from_str_radix(digits, 8)
+            .ok()
+            .filter(|m| *m <= 0o777)
+            .ok_or_else(|| format!("无效的权限值: {}", self.mode))
+    }
+}
+
+/// TLS配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TlsConfig {
+    /// PEM格式证书链路径
+    pub cert_path: String,
+    /// PEM格式私钥路径
+    pub key_path: String,
+    /// 客户端CA证书路径，配置后启用双向TLS，要求客户端提供证书
+    pub client_ca_path: Option<String>,
+    /// 检查证书文件变化的间隔（秒），文件更新后自动重新加载
+    #[serde(default = "default_tls_reload_interval")]
+    pub reload_interval_secs: u64,
+}
+
+fn default_tls_reload_interval() -> u64 {
+    10
+}
+
+/// 单个工具的配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ToolConfig {
+    /// 是否启用，禁用后不出现在 tools/list 中，也不能被调用
+    #[serde(default = "default_tool_enabled")]
+    pub enabled: bool,
+    /// 参数默认值，调用时未传入的参数使用这里的值
+    #[serde(default)]
+    pub defaults: serde_json::Map<String, serde_json::Value>,
+    /// 该工具的调用频率限制，覆盖全局 rate_limit
+    pub rate_limit: Option<RateLimitConfig>,
+    /// 该工具的调用超时（毫秒），覆盖全局 tool_timeout_ms
+    pub timeout_ms: Option<u64>,
+    /// 同时执行的最大调用数，超出的调用排队等待，不配置时不限制
+    pub max_concurrency: Option<usize>,
+    /// 结果缓存时间（秒），仅只读工具可配置，不配置时不缓存
+    pub cache_ttl_secs: Option<u64>,
+    /// 调用前需要操作员在审批页面批准
+    #[serde(default)]
+    pub require_approval: bool,
+    /// 参数匹配其中任一规则时无需审批；键为参数名，值为支持 * 通配的模式
+    #[serde(default)]
+    pub approval_exempt: Vec<HashMap<String, String>>,
+}
+
+/// 令牌桶限流配置，按调用方（认证身份或会话）与工具分别计数
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RateLimitConfig {
+    /// 每分钟补充的调用次数
+    pub per_minute: u32,
+    /// 允许的突发调用次数，默认等于 per_minute
+    pub burst: Option<u32>,
+}
+
+fn default_tool_enabled() -> bool {
+    true
+}
+
+impl Config {
+    /// 工具是否启用
+    pub fn tool_enabled(&self, name: &str) -> bool {
+        self.tools.get(name).is_none_or(|tool| tool.enabled)
+    }
+
+    /// 工具调用是否需要人工审批
+    pub fn tool_requires_approval(&self, name: &str) -> bool {
+        self.tools
+            .get(name)
+            .is_some_and(|tool| tool.require_approval)
+    }
+
+    /// 工具生效的调用超时
+    pub fn tool_timeout(&self, name: &str) -> Duration {
+        let timeout_ms = self
+            .tools
+            .get(name)
+            .and_then(|tool| tool.timeout_ms)
+            .unwrap_or(self.tool_timeout_ms);
+        Duration::from_millis(timeout_ms)
+    }
+
+    /// 工具生效的限流配置，单独配置优先于全局配置
+    pub fn tool_rate_limit(&self, name: &str) -> Option<&RateLimitConfig> {
+        self.tools
+            .get(name)
+            .and_then(|tool| tool.rate_limit.as_ref())
+            .or(self.rate_limit.as_ref())
+    }
+
+    /// 工具结果的缓存时间，未配置时返回 None
+    pub fn tool_cache_ttl(&self, name: &str) -> Option<Duration> {
+        self.tools
+            .get(name)
+            .and_then(|tool| tool.cache_ttl_secs)
+            .map(Duration::from_secs)
+    }
+}
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    /// 便于阅读的文本格式
+    #[default]
+    Pretty,
+    /// 每行一个JSON对象，便于日志系统采集
+    Json,
+}
+
+/// 运行日志与链路追踪配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LoggingConfig {
+    #[serde(default)]
+    pub format: LogFormat,
+    /// 日志级别过滤，语法同 RUST_LOG（如 "info,local_mcp_server=debug"），设置 RUST_LOG 时以其为准
+    #[serde(default = "default_log_level")]
+    pub level: String,
+    /// OTLP/HTTP 收集器地址，如 "http://127.0.0.1:4318"；需要以 otlp 特性编译
+    pub otlp_endpoint: Option<String>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            level: default_log_level(),
+            otlp_endpoint: None,
+        }
+    }
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+/// 人工审批配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ApprovalConfig {
+    /// 等待审批的最长时间（秒），超时视为拒绝
+    #[serde(default = "default_approval_timeout")]
+    pub timeout_secs: u64,
+    /// 审批接口令牌，通过 Authorization: Bearer 携带；有工具设置 require_approval 时必须配置，MCP 认证令牌不能用于审批
+    pub token: Option<String>,
+}
+
+impl Default for ApprovalConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_approval_timeout(),
+            token: None,
+        }
+    }
+}
+
+fn default_approval_timeout() -> u64 {
+    120
+}
+
+/// 文件工具的路径访问策略，对规范化（解析 ".." 与符号链接）后的绝对路径生效
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PathPolicyConfig {
+    /// 允许访问的根目录，为空时不限制目录
+    #[serde(default)]
+    pub allowed_roots: Vec<String>,
+    /// 禁止访问的路径 glob，"**" 匹配任意层目录；默认禁止 SSH/GPG 目录与私钥文件
+    #[serde(default = "default_deny_globs")]
+    pub deny: Vec<String>,
+}
+
+impl Default for PathPolicyConfig {
+    fn default() -> Self {
+        Self {
+            allowed_roots: Vec::new(),
+            deny: default_deny_globs(),
+        }
+    }
+}
+
+fn default_deny_globs() -> Vec<String> {
+    [
+        "**/.ssh/**",
+        "**/.gnupg/**",
+        "**/.aws/**",
+        "**/*.pem",
+        "**/*.key",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+/// 审计日志配置，每次 tools/call 追加一行 JSON
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AuditConfig {
+    /// 日志文件路径，目录不存在时自动创建
+    pub path: String,
+    /// 单个文件达到该大小（MB）后轮转
+    #[serde(default = "default_audit_max_size")]
+    pub max_size_mb: u64,
+    /// 保留的历史文件数，轮转后命名为 <path>.1、<path>.2 ...
+    #[serde(default = "default_audit_max_files")]
+    pub max_files: usize,
+    /// 按工具名配置需要脱敏的参数名，"*" 对所有工具生效
+    #[serde(default)]
+    pub redact: HashMap<String, Vec<String>>,
+}
+
+fn default_audit_max_size() -> u64 {
+    10
+}
+
+fn default_audit_max_files() -> usize {
+    5
+}
+
+impl AuditConfig {
+    /// 该参数是否需要脱敏
+    pub fn should_redact(&self, tool: &str, argument: &str) -> bool {
+        [tool, "*"].iter().any(|key| {
+            self.redact
+                .get(*key)
+                .is_some_and(|names| names.iter().any(|name| name == argument))
+        })
+    }
+}
+
+/// 认证配置
+#[derive(Debug, Deserialize)]
+pub struct AuthConfig {
+    /// 静态令牌列表，可通过 `Authorization: Bearer <token>` 或 `X-API-Key` 请求头携带
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
+    /// OAuth 2.1 受保护资源配置，校验授权服务器签发的JWT访问令牌
+    pub oauth: Option<OAuthConfig>,
+}
+
+/// 单个静态令牌
+#[derive(Debug, Deserialize)]
+pub struct TokenConfig {
+    /// 调用方名称，用于标识身份
+    pub name: String,
+    pub token: String,
+    /// 允许调用的工具，不配置表示允许全部工具
+    pub tools: Option<Vec<String>>,
+}
+
+/// OAuth 2.1 受保护资源配置
+#[derive(Debug, Deserialize)]
+pub struct OAuthConfig {
+    /// 本服务的规范资源URI，如 "https://mcp.example.lan/"
+    pub resource: String,
+    /// 授权服务器列表，写入受保护资源元数据
+    #[serde(default)]
+    pub authorization_servers: Vec<String>,
+    /// 期望的令牌签发者，同时用于发现JWKS地址
+    pub issuer: Option<String>,
+    /// 期望的受众，默认为 resource
+    #[serde(default)]
+    pub audiences: Vec<String>,
+    /// 本地JWKS文件路径，优先于 jwks_uri
+    pub jwks_path: Option<String>,
+    /// 远程JWKS地址，未配置时通过 issuer 的元数据发现
+    pub jwks_uri: Option<String>,
+    /// 访问令牌必须包含的scope
+    #[serde(default)]
+    pub required_scopes: Vec<String>,
+    /// scope到工具的映射，"*" 表示全部工具；为空时不按scope限制工具
+    #[serde(default)]
+    pub scope_tools: HashMap<String, Vec<String>>,
+}
+
+/// 当前请求所属路由生效的配置
+pub fn current() -> Arc<Config> {
+    state::current().config()
 }
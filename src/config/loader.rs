@@ -0,0 +1,478 @@
+use std::{
+    collections::HashSet,
+    fmt, io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use serde_yaml::{Mapping, Value};
+use url::Url;
+
+use crate::{
+    config::{Config, config::RateLimitConfig},
+    tools::{path_policy, registry::ToolRegistry},
+};
+
+/// 环境变量覆盖前缀，嵌套键用双下划线分隔，如 LOCAL_MCP_TLS__CERT_PATH
+const ENV_PREFIX: &str = "LOCAL_MCP_";
+/// 指定配置文件路径的环境变量，不作为配置项覆盖
+const ENV_CONFIG_PATH: &str = "LOCAL_MCP_CONFIG";
+const DEFAULT_CONFIG_PATH: &str = "config.yaml";
+const DEFAULT_LISTEN_PORT: u16 = 3000;
+
+/// 命令行参数
+#[derive(Debug, Parser)]
+#[command(name = "local_mcp_server", version, about = "本地 MCP 服务器")]
+pub struct Cli {
+    /// 配置文件路径，默认为当前目录下的 config.yaml
+    #[arg(short, long, env = ENV_CONFIG_PATH)]
+    pub config: Option<PathBuf>,
+    /// 覆盖配置中的 listen_port
+    #[arg(short = 'p', long)]
+    pub listen_port: Option<u16>,
+    /// 覆盖配置中的 bind_addresses，可重复指定
+    #[arg(short, long = "bind")]
+    pub bind: Vec<IpAddr>,
+}
+
+/// 配置来源，热加载时按同一来源重新读取
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    /// 显式指定的配置文件不存在时报错；默认路径不存在时使用默认配置
+    path_explicit: bool,
+    /// 命令行覆盖项，优先级高于环境变量与配置文件
+    overrides: Vec<(String, Value)>,
+}
+
+impl ConfigSource {
+    /// 从指定的配置文件加载，文件不存在时报错
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            path_explicit: true,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl From<Cli> for ConfigSource {
+    fn from(cli: Cli) -> Self {
+        let mut overrides = Vec::new();
+        if let Some(listen_port) = cli.listen_port {
+            overrides.push(("listen_port".to_string(), Value::from(listen_port)));
+        }
+        if !cli.bind.is_empty() {
+            overrides.push((
+                "bind_addresses".to_string(),
+                Value::Sequence(
+                    cli.bind
+                        .iter()
+                        .map(|a| Value::from(a.to_string()))
+                        .collect(),
+                ),
+            ));
+        }
+
+        Self {
+            path_explicit: cli.config.is_some(),
+            path: cli
+                .config
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH)),
+            overrides,
+        }
+    }
+}
+
+/// 配置加载错误
+#[derive(Debug)]
+pub enum ConfigError {
+    /// 读取配置文件失败
+    Read { path: PathBuf, error: io::Error },
+    /// YAML语法错误
+    Syntax {
+        path: PathBuf,
+        error: serde_yaml::Error,
+    },
+    /// 某个配置项的值无效，key 为配置项路径，如 "tls.cert_path"
+    Invalid { key: String, message: String },
+}
+
+impl ConfigError {
+    fn invalid(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read { path, error } => {
+                write!(f, "读取配置文件 {} 失败: {}", path.display(), error)
+            }
+            Self::Syntax { path, error } => {
+                write!(f, "配置文件 {} 格式错误: {}", path.display(), error)
+            }
+            Self::Invalid { key, message } => write!(f, "配置项 `{}` 无效: {}", key, message),
+        }
+    }
+}
+
+fn read_config_file(source: &ConfigSource) -> Result<Value, ConfigError> {
+    let text = match std::fs::read_to_string(&source.path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !source.path_explicit => {
+            let mut defaults = Mapping::new();
+            defaults.insert("listen_port".into(), DEFAULT_LISTEN_PORT.into());
+            return Ok(Value::Mapping(defaults));
+        }
+        Err(error) => {
+            return Err(ConfigError::Read {
+                path: source.path.clone(),
+                error,
+            });
+        }
+    };
+
+    match serde_yaml::from_str(&text) {
+        // 空文件视为空配置
+        Ok(Value::Null) => Ok(Value::Mapping(Mapping::new())),
+        Ok(value) => Ok(value),
+        Err(error) => Err(ConfigError::Syntax {
+            path: source.path.clone(),
+            error,
+        }),
+    }
+}
+
+/// 按路径设置配置值，中间层不存在或不是映射时自动创建
+fn set_path(root: &mut Value, path: &[String], new_value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+
+    let mut current = root;
+    for segment in parents {
+        if !current.is_mapping() {
+            *current = Value::Mapping(Mapping::new());
+        }
+        let Value::Mapping(map) = current else {
+            unreachable!()
+        };
+        current = map
+            .entry(Value::from(segment.as_str()))
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+    }
+
+    if !current.is_mapping() {
+        *current = Value::Mapping(Mapping::new());
+    }
+    if let Value::Mapping(map) = current {
+        map.insert(Value::from(last.as_str()), new_value);
+    }
+}
+
+/// 环境变量名中的工具名：小写，空格与 "-" 写作 "_"，如 "read ip" 对应 READ_IP
+fn env_tool_name(name: &str) -> String {
+    name.to_lowercase().replace([' ', '-'], "_")
+}
+
+/// 应用 LOCAL_MCP_* 环境变量覆盖，值按YAML标量解析（如 "3000" 解析为数字）；
+/// tools 下的工具名按 env_tool_name 对应到注册的工具，如 LOCAL_MCP_TOOLS__READ_IP__ENABLED
+fn apply_env_overrides(
+    root: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+    registry: &ToolRegistry,
+) {
+    let mut overrides: Vec<(String, String)> = vars
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX) && name != ENV_CONFIG_PATH)
+        .collect();
+    // 保证覆盖顺序稳定，父级键先于子级键应用
+    overrides.sort();
+
+    for (name, raw) in overrides {
+        let mut path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split("__")
+            .map(str::to_lowercase)
+            .collect();
+        if path.len() > 1
+            && path[0] == "tools"
+            && let Some(tool) = registry.names().find(|tool| env_tool_name(tool) == path[1])
+        {
+            path[1] = tool.to_string();
+        }
+        let value = serde_yaml::from_str(&raw).unwrap_or(Value::String(raw));
+        set_path(root, &path, value);
+    }
+}
+
+fn check_file_exists(key: &str, path: &str) -> Result<(), ConfigError> {
+    if Path::new(path).is_file() {
+        Ok(())
+    } else {
+        Err(ConfigError::invalid(key, format!("文件不存在: {}", path)))
+    }
+}
+
+fn check_rate_limit(key: &str, rate_limit: &RateLimitConfig) -> Result<(), ConfigError> {
+    if rate_limit.per_minute == 0 {
+        return Err(ConfigError::invalid(
+            format!("{}.per_minute", key),
+            "必须大于 0",
+        ));
+    }
+    if rate_limit.burst == Some(0) {
+        return Err(ConfigError::invalid(format!("{}.burst", key), "必须大于 0"));
+    }
+    Ok(())
+}
+
+/// 按工具注册表校验反序列化后的配置，错误信息指明具体配置项；不要求配置监听地址，便于嵌入其他服务
+pub fn validate(config: &Config, registry: &ToolRegistry) -> Result<(), ConfigError> {
+    if config.listen_port.is_some() && config.bind_addresses.is_empty() {
+        return Err(ConfigError::invalid("bind_addresses", "不能为空"));
+    }
+
+    if let Some(unix_socket) = &config.unix_socket {
+        unix_socket
+            .permissions_mode()
+            .map_err(|e| ConfigError::invalid("unix_socket.mode", e))?;
+    }
+
+    if let Some(tls) = &config.tls {
+        check_file_exists("tls.cert_path", &tls.cert_path)?;
+        check_file_exists("tls.key_path", &tls.key_path)?;
+        if let Some(ca_path) = &tls.client_ca_path {
+            check_file_exists("tls.client_ca_path", ca_path)?;
+        }
+    }
+
+    if let Some(rate_limit) = &config.rate_limit {
+        check_rate_limit("rate_limit", rate_limit)?;
+    }
+    if let Some(rate_limit) = &config.session_rate_limit {
+        check_rate_limit("session_rate_limit", rate_limit)?;
+    }
+
+    if let Some(audit) = &config.audit {
+        if audit.path.trim().is_empty() {
+            return Err(ConfigError::invalid("audit.path", "不能为空"));
+        }
+        if audit.max_size_mb == 0 {
+            return Err(ConfigError::invalid("audit.max_size_mb", "必须大于 0"));
+        }
+        for tool in audit.redact.keys() {
+            if tool != "*" && registry.get(tool).is_none() {
+                return Err(ConfigError::invalid(
+                    format!("audit.redact.{}", tool),
+                    "未知的工具",
+                ));
+            }
+        }
+    }
+
+    tracing_subscriber::EnvFilter::try_new(&config.logging.level)
+        .map_err(|e| ConfigError::invalid("logging.level", e.to_string()))?;
+    if let Some(endpoint) = &config.logging.otlp_endpoint {
+        Url::parse(endpoint)
+            .map_err(|e| ConfigError::invalid("logging.otlp_endpoint", e.to_string()))?;
+    }
+
+    for (index, root) in config.path_policy.allowed_roots.iter().enumerate() {
+        if root.trim().is_empty() {
+            return Err(ConfigError::invalid(
+                format!("path_policy.allowed_roots[{}]", index),
+                "不能为空",
+            ));
+        }
+    }
+    for (index, pattern) in config.path_policy.deny.iter().enumerate() {
+        path_policy::compile_glob(pattern).map_err(|e| {
+            ConfigError::invalid(format!("path_policy.deny[{}]", index), e.to_string())
+        })?;
+    }
+
+    if config.approval.timeout_secs == 0 {
+        return Err(ConfigError::invalid("approval.timeout_secs", "必须大于 0"));
+    }
+    match config.approval.token.as_deref() {
+        Some("") => return Err(ConfigError::invalid("approval.token", "不能为空")),
+        None if config.tools.values().any(|tool| tool.require_approval) => {
+            return Err(ConfigError::invalid(
+                "approval.token",
+                "有工具设置了 require_approval 时必须配置",
+            ));
+        }
+        _ => {}
+    }
+
+    if config.tool_timeout_ms == 0 {
+        return Err(ConfigError::invalid("tool_timeout_ms", "必须大于 0"));
+    }
+
+    for (name, tool_config) in &config.tools {
+        let Some(entry) = registry.get(name) else {
+            return Err(ConfigError::invalid(
+                format!("tools.{}", name),
+                "未知的工具",
+            ));
+        };
+        let properties = entry.tool.input_schema.properties.as_ref();
+        for key in tool_config.defaults.keys() {
+            if properties.and_then(|p| p.get(key)).is_none() {
+                return Err(ConfigError::invalid(
+                    format!("tools.{}.defaults.{}", name, key),
+                    "工具没有该参数",
+                ));
+            }
+        }
+        if tool_config.timeout_ms == Some(0) {
+            return Err(ConfigError::invalid(
+                format!("tools.{}.timeout_ms", name),
+                "必须大于 0",
+            ));
+        }
+        if tool_config.max_concurrency == Some(0) {
+            return Err(ConfigError::invalid(
+                format!("tools.{}.max_concurrency", name),
+                "必须大于 0",
+            ));
+        }
+        if let Some(rate_limit) = &tool_config.rate_limit {
+            check_rate_limit(&format!("tools.{}.rate_limit", name), rate_limit)?;
+        }
+        if let Some(ttl) = tool_config.cache_ttl_secs {
+            let key = format!("tools.{}.cache_ttl_secs", name);
+            if ttl == 0 {
+                return Err(ConfigError::invalid(key, "必须大于 0"));
+            }
+            if !entry.read_only() {
+                return Err(ConfigError::invalid(key, "只有只读工具可以缓存结果"));
+            }
+            if !entry.cacheable() {
+                return Err(ConfigError::invalid(
+                    key,
+                    "工具结果随时间或随机变化，不能缓存",
+                ));
+            }
+        }
+        for (index, rule) in tool_config.approval_exempt.iter().enumerate() {
+            for key in rule.keys() {
+                if properties.and_then(|p| p.get(key)).is_none() {
+                    return Err(ConfigError::invalid(
+                        format!("tools.{}.approval_exempt[{}].{}", name, index, key),
+                        "工具没有该参数",
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(auth) = &config.auth {
+        let mut seen = HashSet::new();
+        for (index, token) in auth.tokens.iter().enumerate() {
+            if token.token.is_empty() {
+                return Err(ConfigError::invalid(
+                    format!("auth.tokens[{}].token", index),
+                    "不能为空",
+                ));
+            }
+            if !seen.insert(token.token.as_str()) {
+                return Err(ConfigError::invalid(
+                    format!("auth.tokens[{}].token", index),
+                    "与其他令牌重复",
+                ));
+            }
+        }
+
+        if let Some(oauth) = &auth.oauth {
+            Url::parse(&oauth.resource)
+                .map_err(|e| ConfigError::invalid("auth.oauth.resource", e.to_string()))?;
+            if oauth.jwks_path.is_none() && oauth.jwks_uri.is_none() && oauth.issuer.is_none() {
+                return Err(ConfigError::invalid(
+                    "auth.oauth",
+                    "需要配置 jwks_path、jwks_uri 或 issuer 之一",
+                ));
+            }
+            if let Some(jwks_path) = &oauth.jwks_path {
+                check_file_exists("auth.oauth.jwks_path", jwks_path)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 加载配置：配置文件（不存在时使用默认值） < 环境变量 < 命令行参数，最后统一校验
+pub fn load(source: &ConfigSource, registry: &ToolRegistry) -> Result<Config, ConfigError> {
+    let mut value = read_config_file(source)?;
+    apply_env_overrides(&mut value, std::env::vars(), registry);
+    for (key, override_value) in &source.overrides {
+        set_path(
+            &mut value,
+            std::slice::from_ref(key),
+            override_value.clone(),
+        );
+    }
+
+    let config: Config = serde_path_to_error::deserialize(value).map_err(|e| {
+        let key = e.path().to_string();
+        ConfigError::invalid(key, e.into_inner().to_string())
+    })?;
+
+    // 独立运行时需要至少一个监听地址
+    if config.listen_port.is_none() && config.unix_socket.is_none() {
+        return Err(ConfigError::invalid(
+            "listen_port",
+            "listen_port 与 unix_socket 至少需要配置一个",
+        ));
+    }
+    validate(&config, registry)?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::registry::builtin_tools;
+
+    fn overridden(yaml: &str, vars: &[(&str, &str)]) -> Config {
+        let mut value = serde_yaml::from_str(yaml).unwrap();
+        let vars = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()));
+        apply_env_overrides(&mut value, vars, &ToolRegistry::new(builtin_tools()));
+        serde_yaml::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn env_overrides_map_tool_names_with_spaces() {
+        let config = overridden(
+            "tools:\n  read ip:\n    cache_ttl_secs: 5",
+            &[
+                ("LOCAL_MCP_TOOLS__READ_IP__ENABLED", "false"),
+                ("LOCAL_MCP_TOOLS__CAT_FILE__REQUIRE_APPROVAL", "true"),
+                ("LOCAL_MCP_TOOLS__GET_CURRENT_TIME__ENABLED", "false"),
+                ("LOCAL_MCP_LISTEN_PORT", "3100"),
+            ],
+        );
+        assert_eq!(config.listen_port, Some(3100));
+        assert!(!config.tool_enabled("read ip"));
+        // 与配置文件中的同一工具合并，而不是新增一个键
+        assert_eq!(config.tools["read ip"].cache_ttl_secs, Some(5));
+        assert!(config.tools["cat file"].require_approval);
+        assert!(!config.tool_enabled("get_current_time"));
+        assert!(!config.tools.contains_key("read_ip"));
+    }
+
+    #[test]
+    fn unknown_tool_names_are_kept_for_validation() {
+        let config = overridden("{}", &[("LOCAL_MCP_TOOLS__NO_SUCH__ENABLED", "false")]);
+        assert!(config.tools.contains_key("no_such"));
+    }
+}
@@ -1,2 +1,5 @@
+#[allow(clippy::module_inception)]
 pub mod config;
+pub mod loader;
+pub mod reload;
 pub use config::Config;
@@ -0,0 +1,163 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{Duration, SystemTime},
+};
+
+use tokio::sync::mpsc;
+
+use crate::{
+    config::{
+        Config, config,
+        loader::{self, ConfigSource},
+    },
+    server::state,
+    tools::{cache, handler, notifier},
+};
+
+/// 配置中影响客户端可见工具的部分，变化时需要发送 tools/list_changed
+fn tool_visibility(config: &Config) -> serde_json::Value {
+    let tokens: BTreeMap<&str, Option<BTreeSet<&String>>> = config
+        .auth
+        .iter()
+        .flat_map(|auth| auth.tokens.iter())
+        .map(|token| {
+            (
+                token.name.as_str(),
+                token.tools.as_ref().map(|tools| tools.iter().collect()),
+            )
+        })
+        .collect();
+    let scope_tools: BTreeMap<&String, BTreeSet<&String>> = config
+        .auth
+        .iter()
+        .filter_map(|auth| auth.oauth.as_ref())
+        .flat_map(|oauth| oauth.scope_tools.iter())
+        .map(|(scope, tools)| (scope, tools.iter().collect()))
+        .collect();
+    // 参数默认值会写入工具的 inputSchema
+    let tool_defaults: BTreeMap<&String, &serde_json::Map<String, serde_json::Value>> = config
+        .tools
+        .iter()
+        .map(|(name, tool)| (name, &tool.defaults))
+        .collect();
+
+    serde_json::json!({
+        "tools": handler::available_tool_names(config),
+        "tool_defaults": tool_defaults,
+        "tokens": tokens,
+        "scope_tools": scope_tools,
+    })
+}
+
+/// 只在启动时生效的配置项，变化后提示需要重启
+fn restart_required_keys(old: &Config, new: &Config) -> Vec<&'static str> {
+    let mut keys = Vec::new();
+    if old.listen_port != new.listen_port {
+        keys.push("listen_port");
+    }
+    if old.bind_addresses != new.bind_addresses {
+        keys.push("bind_addresses");
+    }
+    if old.unix_socket != new.unix_socket {
+        keys.push("unix_socket");
+    }
+    if old.tls != new.tls {
+        keys.push("tls");
+    }
+    if old.logging != new.logging {
+        keys.push("logging");
+    }
+    keys
+}
+
+/// 重新加载配置：校验通过后原子替换，失败时保留当前配置
+fn reload(source: &ConfigSource) {
+    let state = state::current();
+    let new_config = match loader::load(source, &state.registry) {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!(error = %e, "Config reload rejected, keeping current config");
+            return;
+        }
+    };
+
+    let new_visibility = tool_visibility(&new_config);
+    let old_config = state.replace_config(new_config);
+    let current = state.config();
+    // 缓存的结果可能依赖旧配置（参数默认值、文件访问范围等）
+    cache::clear();
+
+    tracing::info!(path = %source.path.display(), "Config reloaded");
+    for key in restart_required_keys(&old_config, &current) {
+        tracing::warn!(key, "Config key changed, restart required to apply");
+    }
+
+    if tool_visibility(&old_config) != new_visibility {
+        notifier::notify_tools_list_changed();
+    }
+}
+
+fn file_modified(source: &ConfigSource) -> Option<SystemTime> {
+    std::fs::metadata(&source.path)
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+/// 定期检查配置文件修改时间，变化时触发重新加载
+async fn watch_file(source: ConfigSource, trigger: mpsc::UnboundedSender<()>) {
+    let mut last_modified = file_modified(&source);
+    loop {
+        let interval = config::current().config_watch_interval_secs;
+        if interval == 0 {
+            // 未启用文件监听时只记录修改时间，之后通过 SIGHUP 开启监听也不会误触发
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            last_modified = file_modified(&source);
+            continue;
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        let modified = file_modified(&source);
+        if modified != last_modified {
+            last_modified = modified;
+            if trigger.send(()).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn watch_sighup(trigger: mpsc::UnboundedSender<()>) {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            tracing::error!(error = %e, "Error installing SIGHUP handler");
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        if trigger.send(()).is_err() {
+            return;
+        }
+    }
+}
+
+/// 为当前路由启动配置热加载：监听配置文件变化与 SIGHUP 信号
+pub fn spawn(source: ConfigSource) {
+    let (trigger, mut reloads) = mpsc::unbounded_channel();
+
+    state::spawn(watch_file(source.clone(), trigger.clone()));
+    #[cfg(unix)]
+    tokio::spawn(watch_sighup(trigger));
+
+    state::spawn(async move {
+        while reloads.recv().await.is_some() {
+            // 编辑器保存时可能分多次写入，稍等片刻并合并连续的触发
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            while reloads.try_recv().is_ok() {}
+            reload(&source);
+        }
+    });
+}
@@ -0,0 +1,21 @@
+//! 本地 MCP 服务器库：工具 DTO、MCP 消息分发、工具注册表与路由构建
+//!
+//! 通过 [`McpServerBuilder`] 选择内置工具、注册自定义工具，生成 axum 路由或直接运行服务；
+//! 自定义工具可以用 [`mcp_tool`] 宏由带类型参数的函数定义。
+
+// 让 #[mcp_tool] 生成的 ::local_mcp_server 路径在本 crate 内同样可用
+extern crate self as local_mcp_server;
+
+pub mod config;
+pub mod middleware;
+pub mod router;
+pub mod server;
+pub mod tools;
+
+pub use local_mcp_server_macros::mcp_tool;
+pub use server::builder::McpServerBuilder;
+pub use tools::{
+    context::ToolContext,
+    error::ToolError,
+    registry::{ToolEntry, ToolRegistry},
+};
@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    config::config::{self, AuthConfig, TokenConfig},
+    middleware::oauth::{self, OAuthError},
+};
+
+/// 通过认证的调用方身份
+#[derive(Debug, Clone)]
+pub struct AuthIdentity {
+    pub name: String,
+    /// 允许调用的工具，None 表示不限制
+    pub allowed_tools: Option<HashSet<String>>,
+}
+
+impl AuthIdentity {
+    /// 判断该身份是否可以调用指定工具
+    pub fn can_use_tool(&self, tool_name: &str) -> bool {
+        self.allowed_tools
+            .as_ref()
+            .is_none_or(|tools| tools.contains(tool_name))
+    }
+}
+
+impl From<&TokenConfig> for AuthIdentity {
+    fn from(token: &TokenConfig) -> Self {
+        Self {
+            name: token.name.clone(),
+            allowed_tools: token
+                .tools
+                .as_ref()
+                .map(|tools| tools.iter().cloned().collect()),
+        }
+    }
+}
+
+/// 从请求头中提取令牌，支持 Bearer 与 X-API-Key 两种方式
+pub fn extract_token(headers: &HeaderMap) -> Option<&str> {
+    if let Some(value) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+    {
+        let (scheme, token) = value.split_once(' ')?;
+        return scheme.eq_ignore_ascii_case("bearer").then(|| token.trim());
+    }
+
+    headers.get("x-api-key").and_then(|h| h.to_str().ok())
+}
+
+/// 常量时间比较，避免通过响应时间猜测令牌
+pub fn token_eq(expected: &str, provided: &str) -> bool {
+    expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// 构造 WWW-Authenticate 质询，配置了 OAuth 时附带受保护资源元数据地址
+fn challenge(auth: &AuthConfig, error: Option<&OAuthError>) -> String {
+    let mut params = vec!["realm=\"local_mcp_server\"".to_string()];
+    if let Some(oauth) = &auth.oauth {
+        params.push(format!(
+            "resource_metadata=\"{}\"",
+            oauth::resource_metadata_url(oauth)
+        ));
+    }
+    match error {
+        Some(OAuthError::InvalidToken(_)) => params.push("error=\"invalid_token\"".to_string()),
+        Some(OAuthError::InsufficientScope(scope)) => {
+            params.push("error=\"insufficient_scope\"".to_string());
+            params.push(format!("scope=\"{}\"", scope));
+        }
+        None => {}
+    }
+    format!("Bearer {}", params.join(", "))
+}
+
+fn reject(auth: &AuthConfig, error: Option<OAuthError>) -> Response {
+    let (status, message, description) = match &error {
+        Some(OAuthError::InsufficientScope(_)) => (
+            StatusCode::FORBIDDEN,
+            "Forbidden",
+            "令牌缺少所需的scope".to_string(),
+        ),
+        Some(OAuthError::InvalidToken(description)) => (
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized",
+            description.clone(),
+        ),
+        None => (
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized",
+            "缺少访问令牌".to_string(),
+        ),
+    };
+
+    let mut response = (
+        status,
+        axum::Json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {
+                "code": -32001,
+                "message": message,
+                "data": { "description": description }
+            }
+        })),
+    )
+        .into_response();
+    if let Ok(value) = HeaderValue::from_str(&challenge(auth, error.as_ref())) {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, value);
+    }
+    response
+}
+
+/// 按认证配置校验令牌：先匹配静态令牌，再按 OAuth 校验JWT
+pub async fn authenticate(auth: &AuthConfig, token: &str) -> Result<AuthIdentity, OAuthError> {
+    match auth
+        .tokens
+        .iter()
+        .find(|candidate| token_eq(&candidate.token, token))
+    {
+        Some(token_config) => Ok(AuthIdentity::from(token_config)),
+        None => match &auth.oauth {
+            Some(oauth_config) => oauth::validate_access_token(oauth_config, token).await,
+            None => Err(OAuthError::InvalidToken("令牌无效".to_string())),
+        },
+    }
+}
+
+/// 认证中间件：校验请求携带的令牌并写入调用方身份；未配置 auth 时直接放行
+pub async fn require_auth(mut request: Request, next: Next) -> Response {
+    let config = config::current();
+    let Some(auth) = &config.auth else {
+        return next.run(request).await;
+    };
+
+    let Some(token) = extract_token(request.headers()).map(str::to_string) else {
+        return reject(auth, None);
+    };
+
+    match authenticate(auth, &token).await {
+        Ok(identity) => {
+            request.extensions_mut().insert(identity);
+            next.run(request).await
+        }
+        Err(error) => reject(auth, Some(error)),
+    }
+}
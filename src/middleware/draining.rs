@@ -0,0 +1,32 @@
+use axum::{
+    extract::Request,
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::server::shutdown;
+
+/// 关闭期间拒绝新请求，已在处理中的请求不受影响
+pub async fn reject_when_draining(request: Request, next: Next) -> Response {
+    if !shutdown::is_draining() {
+        return next.run(request).await;
+    }
+
+    let mut response = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        axum::Json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {
+                "code": -32000,
+                "message": "Server is shutting down"
+            }
+        })),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::CONNECTION, HeaderValue::from_static("close"));
+    response
+}
@@ -0,0 +1,4 @@
+pub mod auth;
+pub mod draining;
+pub mod oauth;
+pub mod origin;
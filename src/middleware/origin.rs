@@ -0,0 +1,176 @@
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::config::config;
+
+/// 未配置 allowed_origins 时默认允许的来源
+const DEFAULT_ALLOWED_ORIGINS: [&str; 6] = [
+    "http://localhost:*",
+    "http://127.0.0.1:*",
+    "http://[::1]:*",
+    "https://localhost:*",
+    "https://127.0.0.1:*",
+    "https://[::1]:*",
+];
+
+/// 预检响应允许的请求头
+const ALLOWED_HEADERS: &str = "authorization, content-type, accept, x-api-key, mcp-session-id, mcp-protocol-version, last-event-id";
+
+/// 浏览器可读取的响应头
+const EXPOSED_HEADERS: &str = "mcp-session-id, www-authenticate";
+
+/// 判断来源是否匹配单条规则，规则以 ":*" 结尾时匹配任意端口（含默认端口）
+fn origin_matches(pattern: &str, origin: &str) -> bool {
+    if pattern == "*" || pattern.eq_ignore_ascii_case(origin) {
+        return true;
+    }
+
+    let Some(base) = pattern.strip_suffix(":*") else {
+        return false;
+    };
+    if origin.eq_ignore_ascii_case(base) {
+        return true;
+    }
+
+    let Some(prefix) = origin.get(..base.len()) else {
+        return false;
+    };
+    prefix.eq_ignore_ascii_case(base)
+        && origin[base.len()..]
+            .strip_prefix(':')
+            .is_some_and(|port| !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// 按当前配置判断来源是否允许
+pub fn is_origin_allowed(origin: &str) -> bool {
+    let config = config::current();
+    match &config.allowed_origins {
+        Some(origins) => origins
+            .iter()
+            .any(|pattern| origin_matches(pattern, origin)),
+        None => DEFAULT_ALLOWED_ORIGINS
+            .iter()
+            .any(|pattern| origin_matches(pattern, origin)),
+    }
+}
+
+fn insert_cors_headers(headers: &mut HeaderMap, origin: HeaderValue) {
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    headers.insert(
+        header::ACCESS_CONTROL_EXPOSE_HEADERS,
+        HeaderValue::from_static(EXPOSED_HEADERS),
+    );
+    headers.append(header::VARY, HeaderValue::from_static("origin"));
+}
+
+fn forbidden_origin(origin: &str) -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        axum::Json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": null,
+            "error": {
+                "code": -32001,
+                "message": "Forbidden",
+                "data": { "description": format!("不允许的来源: {}", origin) }
+            }
+        })),
+    )
+        .into_response()
+}
+
+fn preflight(origin: HeaderValue) -> Response {
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    let headers = response.headers_mut();
+    insert_cors_headers(headers, origin);
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, POST, DELETE, OPTIONS"),
+    );
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static(ALLOWED_HEADERS),
+    );
+    headers.insert(
+        header::ACCESS_CONTROL_MAX_AGE,
+        HeaderValue::from_static("600"),
+    );
+    response
+}
+
+/// Origin 校验中间件，防止DNS重绑定；同时处理CORS预检并为响应添加CORS头。
+/// 不带 Origin 的请求（非浏览器客户端）直接放行
+pub async fn validate_origin(request: Request, next: Next) -> Response {
+    let Some(origin) = request.headers().get(header::ORIGIN).cloned() else {
+        return next.run(request).await;
+    };
+
+    let origin_str = origin.to_str().unwrap_or_default();
+    if !is_origin_allowed(origin_str) {
+        return forbidden_origin(origin_str);
+    }
+
+    if request.method() == Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+    {
+        return preflight(origin);
+    }
+
+    let mut response = next.run(request).await;
+    insert_cors_headers(response.headers_mut(), origin);
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::origin_matches;
+
+    #[test]
+    fn exact_match_is_case_insensitive() {
+        assert!(origin_matches("https://example.com", "https://example.com"));
+        assert!(origin_matches("https://example.com", "HTTPS://Example.COM"));
+        assert!(!origin_matches(
+            "https://example.com",
+            "https://example.org"
+        ));
+        assert!(!origin_matches("https://example.com", "http://example.com"));
+    }
+
+    #[test]
+    fn wildcard_allows_everything() {
+        assert!(origin_matches("*", "https://anything.example"));
+        assert!(origin_matches("*", "null"));
+    }
+
+    #[test]
+    fn any_port_pattern() {
+        let pattern = "http://localhost:*";
+        assert!(origin_matches(pattern, "http://localhost"));
+        assert!(origin_matches(pattern, "http://localhost:3000"));
+        assert!(origin_matches(pattern, "HTTP://LOCALHOST:8080"));
+        assert!(!origin_matches(pattern, "http://localhost:"));
+        assert!(!origin_matches(pattern, "http://localhost:80a"));
+        assert!(!origin_matches(pattern, "http://localhost.evil.com"));
+        assert!(!origin_matches(pattern, "http://localhost.evil.com:3000"));
+        assert!(!origin_matches(pattern, "https://localhost:3000"));
+        assert!(!origin_matches(pattern, "http://local"));
+    }
+
+    #[test]
+    fn ipv6_any_port_pattern() {
+        assert!(origin_matches("http://[::1]:*", "http://[::1]:3000"));
+        assert!(!origin_matches("http://[::1]:*", "http://[::2]:3000"));
+    }
+
+    #[test]
+    fn non_ascii_origin_does_not_panic() {
+        assert!(!origin_matches("http://localhost:*", "http://lo☃"));
+        assert!(!origin_matches("http://localhost:*", "http://localhos☃:1"));
+    }
+}
@@ -1,43 +1,113 @@
 use axum::{
-    Router,
-    http::StatusCode,
+    Extension, Router,
+    extract::ConnectInfo,
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
     routing::{get, post},
 };
 
+use crate::{
+    middleware::{
+        auth::{AuthIdentity, require_auth},
+        draining::reject_when_draining,
+        origin::validate_origin,
+    },
+    server::peer::PeerAddr,
+    tools::{mcp_handler::request_session, rate_limit::RATE_LIMITED_CODE},
+};
+
 pub fn init_bind_router(app: Router) -> Router {
-    app.route("/", get(|| async { "MCP Server is running!" }))
+    let mcp_routes = Router::new()
         // MCP标准端点 - 初始化和主要通信（通用处理器）
         .route(
             "/",
-            post(crate::tools::mcp_handler::handle_generic_mcp_request),
+            post(crate::tools::mcp_handler::handle_generic_mcp_request)
+                .delete(crate::tools::mcp_handler::handle_delete_session),
         )
         // MCP标准端点 - SSE支持
         .route("/sse", get(crate::tools::sse_handler::handle_sse))
+        // WebSocket端点 - 全双工JSON-RPC
+        .route("/ws", get(crate::tools::ws_handler::handle_ws))
         // 工具端点（向后兼容）
         .route("/tools/list", post(handle_tools_list))
         .route("/tools/call", post(handle_tool_call))
+        // 所有MCP端点都需要通过认证
+        .route_layer(middleware::from_fn(require_auth))
+        // 关闭期间不再接受新的MCP请求
+        .route_layer(middleware::from_fn(reject_when_draining));
+
+    // Prometheus 指标包含工具名与调用量，与MCP端点使用相同的认证；关闭期间仍可抓取
+    let metrics_routes = Router::new()
+        .route("/metrics", get(crate::server::metrics::handle_metrics))
+        .route_layer(middleware::from_fn(require_auth));
+
+    app.route("/", get(|| async { "MCP Server is running!" }))
+        // OAuth 受保护资源元数据，无需认证
+        .route(
+            "/.well-known/oauth-protected-resource",
+            get(crate::middleware::oauth::handle_protected_resource_metadata),
+        )
+        // 存活与就绪检查，无需认证
+        .route("/healthz", get(crate::server::health::handle_healthz))
+        .route("/readyz", get(crate::server::health::handle_readyz))
+        // 人工审批页面与接口，使用 approval.token 而非 MCP 令牌鉴权
+        .route(
+            "/approvals",
+            get(crate::tools::approval::handle_approval_page),
+        )
+        .route(
+            "/approvals/pending",
+            get(crate::tools::approval::handle_list_approvals),
+        )
+        .route(
+            "/approvals/{id}",
+            post(crate::tools::approval::handle_decide),
+        )
+        .merge(mcp_routes)
+        .merge(metrics_routes)
+        // 所有端点共用的 Origin 校验与 CORS 处理，需在认证之前执行以放行预检请求
+        .layer(middleware::from_fn(validate_origin))
 }
 
 /// 工具列表处理器
 async fn handle_tools_list(
+    identity: Option<Extension<AuthIdentity>>,
     json: axum::Json<
         crate::tools::tool_dto::JsonRpcRequest<crate::tools::tool_dto::ToolsListParams>,
     >,
 ) -> axum::Json<crate::tools::tool_dto::JsonRpcResponse<crate::tools::tool_dto::ToolsListResult>> {
-    axum::Json(crate::tools::handler::handle_tools_list_internal(json.0).await)
+    axum::Json(
+        crate::tools::handler::handle_tools_list_internal(json.0, identity.as_ref().map(|i| &i.0))
+            .await,
+    )
 }
 
 /// 工具调用处理器
 async fn handle_tool_call(
+    identity: Option<Extension<AuthIdentity>>,
+    peer: Option<Extension<ConnectInfo<PeerAddr>>>,
+    headers: HeaderMap,
     json: axum::Json<
         crate::tools::tool_dto::JsonRpcRequest<crate::tools::tool_dto::ToolCallParams>,
     >,
-) -> Result<
-    axum::Json<crate::tools::tool_dto::JsonRpcResponse<crate::tools::tool_dto::ToolCallResult>>,
-    (StatusCode, axum::Json<crate::tools::tool_dto::JsonRpcError>),
-> {
-    match crate::tools::handler::handle_tool_call_internal(json.0).await {
-        Ok(response) => Ok(axum::Json(response)),
-        Err(error) => Err((StatusCode::BAD_REQUEST, axum::Json(error))),
+) -> Response {
+    let session_id = match request_session(&headers, identity.as_ref().map(|i| &i.0)) {
+        Ok(session_id) => session_id,
+        Err(error) => return error.into_response(),
+    };
+    match crate::tools::handler::handle_tool_call_internal(
+        json.0,
+        identity.as_ref().map(|i| &i.0),
+        session_id.as_deref(),
+        peer.and_then(|Extension(ConnectInfo(peer))| peer.ip()),
+    )
+    .await
+    {
+        Ok(response) => axum::Json(response).into_response(),
+        Err(error) if error.error.code == RATE_LIMITED_CODE => {
+            (StatusCode::TOO_MANY_REQUESTS, axum::Json(error)).into_response()
+        }
+        Err(error) => (StatusCode::BAD_REQUEST, axum::Json(error)).into_response(),
     }
 }
@@ -0,0 +1,250 @@
+use std::{future::IntoFuture, net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{Router, middleware};
+use tokio::{net::TcpListener, task::JoinSet};
+
+use crate::{
+    config::{
+        Config, config,
+        loader::{self, ConfigSource},
+        reload,
+    },
+    router::router::init_bind_router,
+    server::{
+        health,
+        peer::PeerAddr,
+        shutdown,
+        state::{self, ServerState},
+        telemetry,
+    },
+    tools::{
+        audit,
+        registry::{ToolEntry, ToolRegistry, builtin_tools},
+    },
+};
+
+/// MCP 服务构建器：选择内置工具、注册自定义工具，生成路由或直接运行服务
+///
+/// 每个路由持有自己的工具注册表与配置，同一进程内可以构建多个互不影响的路由。
+pub struct McpServerBuilder {
+    tools: Vec<ToolEntry>,
+}
+
+impl Default for McpServerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpServerBuilder {
+    /// 包含全部内置工具
+    pub fn new() -> Self {
+        Self {
+            tools: builtin_tools(),
+        }
+    }
+
+    /// 不包含任何工具
+    pub fn empty() -> Self {
+        Self { tools: Vec::new() }
+    }
+
+    /// 只保留指定名称的工具
+    pub fn only_tools(mut self, names: &[&str]) -> Self {
+        self.tools
+            .retain(|entry| names.contains(&entry.tool.name.as_str()));
+        self
+    }
+
+    /// 注册工具，与已有工具同名时替换原工具
+    pub fn tool(mut self, entry: ToolEntry) -> Self {
+        self.tools.retain(|e| e.tool.name != entry.tool.name);
+        self.tools.push(entry);
+        self
+    }
+
+    /// 校验配置并生成路由与它持有的状态
+    fn into_state(self, config: Config) -> Result<Arc<ServerState>, String> {
+        let registry = ToolRegistry::new(self.tools);
+        loader::validate(&config, &registry).map_err(|e| e.to_string())?;
+        Ok(ServerState::new(registry, config))
+    }
+
+    /// 校验配置，返回挂载全部 MCP 端点的路由，可合并到调用方自己的 axum 应用中
+    pub fn build_router(self, config: Config) -> Result<Router, String> {
+        let state = self.into_state(config)?;
+        health::mark_started();
+        Ok(router_with_state(state))
+    }
+
+    /// 按配置来源加载配置并运行服务，收到退出信号并排空进行中的请求后返回进程退出码；
+    /// 配置无效时返回错误
+    pub async fn run(self, source: ConfigSource) -> Result<i32, String> {
+        let registry = ToolRegistry::new(self.tools);
+        let config =
+            loader::load(&source, &registry).map_err(|e| format!("Error loading config: {}", e))?;
+
+        let telemetry = telemetry::init(&config.logging);
+        if !source.path.exists() {
+            tracing::info!(
+                "Config file {} not found, using defaults",
+                source.path.display()
+            );
+        }
+
+        health::mark_started();
+        let state = ServerState::new(registry, config);
+        let exit_code = state::scope(state.clone(), async {
+            reload::spawn(source);
+            serve(router_with_state(state)).await
+        })
+        .await;
+
+        audit::flush(Duration::from_secs(2));
+        // 调用方通常随后直接 exit，先手动刷新追踪数据
+        drop(telemetry);
+        Ok(exit_code)
+    }
+}
+
+/// 挂载全部端点，并在最外层把路由状态放入每个请求的任务上下文
+fn router_with_state(state: Arc<ServerState>) -> Router {
+    init_bind_router(Router::new()).layer(middleware::from_fn_with_state(state, state::with_state))
+}
+
+/// 在配置的 TCP 地址与 Unix 套接字上提供服务，直到收到退出信号并完成排空
+async fn serve(app: Router) -> i32 {
+    let config = config::current();
+    shutdown::spawn_signal_handler();
+    let mut servers = JoinSet::new();
+    let shutdown = shutdown::token();
+
+    let tcp_addresses: Vec<SocketAddr> = match config.listen_port {
+        Some(port) => config
+            .bind_addresses
+            .iter()
+            .map(|ip| SocketAddr::new(*ip, port))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    for bind_address in tcp_addresses {
+        let listener = match TcpListener::bind(bind_address).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!("Error binding {}: {}", bind_address, e);
+                return 1;
+            }
+        };
+        tracing::info!(
+            "Listening on {}://{}",
+            if config.tls.is_some() {
+                "https"
+            } else {
+                "http"
+            },
+            bind_address
+        );
+        match &config.tls {
+            Some(tls) => {
+                let listener = match crate::server::tls::TlsListener::new(listener, tls) {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        tracing::error!("Error loading TLS config: {}", e);
+                        return 1;
+                    }
+                };
+                servers.spawn(
+                    axum::serve(
+                        listener,
+                        app.clone()
+                            .into_make_service_with_connect_info::<PeerAddr>(),
+                    )
+                    .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                    .into_future(),
+                );
+            }
+            None => {
+                servers.spawn(
+                    axum::serve(
+                        listener,
+                        app.clone()
+                            .into_make_service_with_connect_info::<PeerAddr>(),
+                    )
+                    .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                    .into_future(),
+                );
+            }
+        }
+    }
+
+    if let Some(unix_socket) = &config.unix_socket {
+        #[cfg(unix)]
+        {
+            let listener = match crate::server::unix_socket::bind_unix_socket(unix_socket) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::error!("Error binding unix socket: {}", e);
+                    return 1;
+                }
+            };
+            tracing::info!("Listening on unix:{}", unix_socket.path);
+            servers.spawn(
+                axum::serve(
+                    listener,
+                    app.clone()
+                        .into_make_service_with_connect_info::<PeerAddr>(),
+                )
+                .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                .into_future(),
+            );
+        }
+        #[cfg(not(unix))]
+        {
+            tracing::error!(
+                "Error in config file: unix_socket 仅支持类 Unix 系统 ({})",
+                unix_socket.path
+            );
+            return 1;
+        }
+    }
+
+    // 等待退出信号；服务异常退出时同样进入关闭流程
+    let mut exit_code = 0;
+    tokio::select! {
+        biased;
+        _ = shutdown.cancelled() => {}
+        Some(result) = servers.join_next() => {
+            tracing::error!("Server stopped unexpectedly: {:?}", result);
+            exit_code = 1;
+            shutdown.cancel();
+        }
+    }
+
+    // 停止接受新连接，等待进行中的请求、工具调用与长连接结束
+    let drain = Duration::from_secs(config::current().shutdown_drain_secs);
+    let in_flight = shutdown::in_flight();
+    in_flight.close();
+    let drained = tokio::time::timeout(drain, async {
+        while servers.join_next().await.is_some() {}
+        in_flight.wait().await;
+    })
+    .await;
+    match drained {
+        Ok(()) => tracing::info!("All in-flight requests finished, exiting"),
+        Err(_) => {
+            tracing::warn!(
+                remaining_calls = in_flight.len(),
+                "Drain period of {}s elapsed, aborting remaining requests",
+                drain.as_secs()
+            );
+            exit_code = 1;
+        }
+    }
+
+    #[cfg(unix)]
+    if let Some(unix_socket) = &config.unix_socket {
+        let _ = std::fs::remove_file(&unix_socket.path);
+    }
+    exit_code
+}
@@ -0,0 +1,229 @@
+use std::{
+    path::Path,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use axum::{Json, http::StatusCode};
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::{
+    config::{Config, config},
+    server::{metrics::metrics, shutdown, state},
+    tools::{
+        path_policy, public::network::read_ip_tool::build_resolver, registry::registry, session,
+    },
+};
+
+static STARTED_AT: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+/// 单个自检的最长时间
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 记录启动时间，启动时调用
+pub fn mark_started() {
+    LazyLock::force(&STARTED_AT);
+}
+
+/// 自检结果
+#[derive(Debug, Serialize)]
+struct Check {
+    name: &'static str,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl Check {
+    fn from_result(name: &'static str, result: Result<(), String>) -> Self {
+        match result {
+            Ok(()) => Self {
+                name,
+                ok: true,
+                message: None,
+            },
+            Err(message) => Self {
+                name,
+                ok: false,
+                message: Some(message),
+            },
+        }
+    }
+}
+
+/// 服务器基本状态，健康检查与就绪检查共用
+fn status(config: &Config) -> Value {
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "uptime_secs": STARTED_AT.elapsed().as_secs(),
+        "tools": {
+            "registered": registry().count(),
+            "enabled": registry().list(config).len(),
+        },
+        "sessions": session::count(),
+        "connections": {
+            "sse": metrics().sse_connections.get(),
+            "websocket": metrics().ws_connections.get(),
+        },
+        "config_loaded_at": state::current().loaded_at(),
+    })
+}
+
+fn check_readable(path: &str) -> Result<(), String> {
+    std::fs::File::open(path)
+        .map(|_| ())
+        .map_err(|e| format!("{}: {}", path, e))
+}
+
+/// 目录是否可写：创建并立即删除一个临时文件，能同时反映权限位、ACL 与只读挂载
+fn dir_writable(dir: &Path) -> bool {
+    let probe = dir.join(format!(".local_mcp_probe_{}", std::process::id()));
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+    {
+        Ok(_) => std::fs::remove_file(&probe).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// 已存在的文件是否可写：以追加方式打开，不截断也不写入内容
+fn file_writable(file: &Path) -> bool {
+    std::fs::OpenOptions::new().append(true).open(file).is_ok()
+}
+
+/// 审计日志目录存在且可写；日志文件已存在时同样需要可写
+fn check_audit_dir(path: &str) -> Result<(), String> {
+    let file = Path::new(path);
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let metadata = std::fs::metadata(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    if !metadata.is_dir() {
+        return Err(format!("{}: 不是目录", dir.display()));
+    }
+    if !dir_writable(dir) {
+        return Err(format!("{}: 目录不可写", dir.display()));
+    }
+    if file.exists() && !file_writable(file) {
+        return Err(format!("{}: 文件不可写", file.display()));
+    }
+    Ok(())
+}
+
+/// 按当前配置执行自检，只检查已启用的功能
+async fn run_checks(config: &Config) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    if config.tool_enabled("read ip") {
+        let result = match tokio::time::timeout(CHECK_TIMEOUT, build_resolver(None, &[])).await {
+            Ok(result) => result.map(|_| ()),
+            Err(_) => Err("创建DNS解析器超时".to_string()),
+        };
+        checks.push(Check::from_result("dns_resolver", result));
+    }
+
+    if !config.path_policy.allowed_roots.is_empty()
+        && (config.tool_enabled("cat file") || config.tool_enabled("list files"))
+    {
+        checks.push(Check::from_result(
+            "path_policy_roots",
+            path_policy::check_roots(&config.path_policy),
+        ));
+    }
+
+    if let Some(audit) = &config.audit {
+        checks.push(Check::from_result(
+            "audit_log_dir",
+            check_audit_dir(&audit.path),
+        ));
+    }
+
+    if let Some(tls) = &config.tls {
+        let result = check_readable(&tls.cert_path)
+            .and_then(|_| check_readable(&tls.key_path))
+            .and_then(|_| match &tls.client_ca_path {
+                Some(ca_path) => check_readable(ca_path),
+                None => Ok(()),
+            });
+        checks.push(Check::from_result("tls_files", result));
+    }
+
+    if let Some(jwks_path) = config
+        .auth
+        .as_ref()
+        .and_then(|auth| auth.oauth.as_ref())
+        .and_then(|oauth| oauth.jwks_path.as_ref())
+    {
+        checks.push(Check::from_result("jwks_file", check_readable(jwks_path)));
+    }
+
+    checks
+}
+
+/// 存活检查：进程能够响应即返回 200
+pub async fn handle_healthz() -> Json<Value> {
+    let mut body = status(&config::current());
+    body["status"] = json!("ok");
+    Json(body)
+}
+
+/// 就绪检查：所有自检通过时返回 200，否则（包括关闭期间）返回 503
+pub async fn handle_readyz() -> (StatusCode, Json<Value>) {
+    let config = config::current();
+    let checks = run_checks(&config).await;
+    let draining = shutdown::is_draining();
+    let ready = !draining && checks.iter().all(|check| check.ok);
+
+    let mut body = status(&config);
+    body["status"] = json!(if draining {
+        "draining"
+    } else if ready {
+        "ready"
+    } else {
+        "not_ready"
+    });
+    body["checks"] = json!(checks);
+
+    let status_code = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status_code, Json(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_dir_check_does_not_create_directory() {
+        let dir = std::env::temp_dir().join(format!("local_mcp_audit_{}", std::process::id()));
+        let log = dir.join("audit.log");
+        let log = log.to_str().unwrap();
+
+        assert!(check_audit_dir(log).is_err());
+        assert!(!dir.exists());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(check_audit_dir(log).is_ok());
+        // 可写探测不留下文件
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn audit_dir_must_be_a_directory() {
+        let file =
+            std::env::temp_dir().join(format!("local_mcp_audit_file_{}", std::process::id()));
+        std::fs::write(&file, b"").unwrap();
+        let log = file.join("audit.log");
+
+        assert!(check_audit_dir(log.to_str().unwrap()).is_err());
+        std::fs::remove_file(&file).unwrap();
+    }
+}
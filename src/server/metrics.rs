@@ -0,0 +1,158 @@
+use std::sync::LazyLock;
+
+use axum::{
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+use crate::tools::registry::registry;
+
+/// 指标中记录的MCP方法，其余方法统一记为 "other"，避免标签基数失控
+const KNOWN_METHODS: &[&str] = &[
+    "initialize",
+    "notifications/initialized",
+    "ping",
+    "tools/list",
+    "tools/call",
+];
+
+/// 服务器运行指标
+pub struct Metrics {
+    registry: Registry,
+    /// 按方法统计的JSON-RPC请求数
+    pub requests: IntCounterVec,
+    /// 按工具与结果统计的工具调用数
+    pub tool_calls: IntCounterVec,
+    /// 按工具统计的调用耗时
+    pub tool_call_duration: HistogramVec,
+    /// 按工具统计的正在执行的调用数
+    pub tool_calls_in_flight: IntGaugeVec,
+    /// 当前SSE连接数
+    pub sse_connections: IntGauge,
+    /// 当前WebSocket连接数
+    pub ws_connections: IntGauge,
+    /// read ip 工具中域名解析与测速的耗时
+    pub resolve_duration: Histogram,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        let requests = IntCounterVec::new(
+            Opts::new("mcp_requests_total", "JSON-RPC requests by method"),
+            &["method"],
+        )
+        .unwrap();
+        let tool_calls = IntCounterVec::new(
+            Opts::new("mcp_tool_calls_total", "Tool calls by tool and outcome"),
+            &["tool", "outcome"],
+        )
+        .unwrap();
+        let tool_call_duration = HistogramVec::new(
+            HistogramOpts::new("mcp_tool_call_duration_seconds", "Tool call latency").buckets(
+                vec![0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0],
+            ),
+            &["tool"],
+        )
+        .unwrap();
+        let tool_calls_in_flight = IntGaugeVec::new(
+            Opts::new("mcp_tool_calls_in_flight", "Tool calls currently executing"),
+            &["tool"],
+        )
+        .unwrap();
+        let sse_connections =
+            IntGauge::new("mcp_sse_connections", "Active SSE connections").unwrap();
+        let ws_connections =
+            IntGauge::new("mcp_ws_connections", "Active WebSocket connections").unwrap();
+        let resolve_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "mcp_read_ip_resolve_duration_seconds",
+                "DNS resolution and latency probing in read ip",
+            )
+            .buckets(vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]),
+        )
+        .unwrap();
+
+        registry.register(Box::new(requests.clone())).unwrap();
+        registry.register(Box::new(tool_calls.clone())).unwrap();
+        registry
+            .register(Box::new(tool_call_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(tool_calls_in_flight.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(sse_connections.clone()))
+            .unwrap();
+        registry.register(Box::new(ws_connections.clone())).unwrap();
+        registry
+            .register(Box::new(resolve_duration.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            requests,
+            tool_calls,
+            tool_call_duration,
+            tool_calls_in_flight,
+            sse_connections,
+            ws_connections,
+            resolve_duration,
+        }
+    }
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// 获取全局指标
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+/// 方法名对应的指标标签
+pub fn method_label(method: &str) -> &str {
+    if KNOWN_METHODS.contains(&method) {
+        method
+    } else {
+        "other"
+    }
+}
+
+/// 工具名对应的指标标签，未注册的工具统一记为 "unknown"
+pub fn tool_label(tool: &str) -> &str {
+    if registry().get(tool).is_some() {
+        tool
+    } else {
+        "unknown"
+    }
+}
+
+/// 存活期间计数加一的守卫，用于统计连接数与进行中的调用
+pub struct GaugeGuard(IntGauge);
+
+impl GaugeGuard {
+    pub fn new(gauge: IntGauge) -> Self {
+        gauge.inc();
+        Self(gauge)
+    }
+}
+
+impl Drop for GaugeGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// Prometheus 文本格式的指标端点
+pub async fn handle_metrics() -> Response {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&METRICS.registry.gather(), &mut buffer) {
+        return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    ([(header::CONTENT_TYPE, encoder.format_type())], buffer).into_response()
+}
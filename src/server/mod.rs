@@ -0,0 +1,10 @@
+pub mod builder;
+pub mod health;
+pub mod metrics;
+pub mod peer;
+pub mod shutdown;
+pub mod state;
+pub mod telemetry;
+pub mod tls;
+#[cfg(unix)]
+pub mod unix_socket;
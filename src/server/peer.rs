@@ -0,0 +1,35 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{extract::connect_info::Connected, serve::IncomingStream};
+use tokio::net::TcpListener;
+
+use crate::server::tls::TlsListener;
+
+/// 连接的对端地址，Unix 域套接字连接没有 IP 地址
+#[derive(Debug, Clone, Copy)]
+pub struct PeerAddr(Option<SocketAddr>);
+
+impl PeerAddr {
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.0.map(|addr| addr.ip())
+    }
+}
+
+impl Connected<IncomingStream<'_, TcpListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, TcpListener>) -> Self {
+        Self(Some(*stream.remote_addr()))
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        Self(Some(*stream.remote_addr()))
+    }
+}
+
+#[cfg(unix)]
+impl Connected<IncomingStream<'_, tokio::net::UnixListener>> for PeerAddr {
+    fn connect_info(_stream: IncomingStream<'_, tokio::net::UnixListener>) -> Self {
+        Self(None)
+    }
+}
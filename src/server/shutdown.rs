@@ -0,0 +1,63 @@
+use std::sync::LazyLock;
+
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+
+/// 进程级关闭信号，收到 SIGINT/SIGTERM 后取消
+static SHUTDOWN: LazyLock<CancellationToken> = LazyLock::new(CancellationToken::new);
+/// 进行中的工具调用与 WebSocket 连接，关闭时等待其完成
+static IN_FLIGHT: LazyLock<TaskTracker> = LazyLock::new(TaskTracker::new);
+
+/// 关闭信号
+pub fn token() -> CancellationToken {
+    SHUTDOWN.clone()
+}
+
+/// 是否正在关闭，关闭期间不再接受新请求
+pub fn is_draining() -> bool {
+    SHUTDOWN.is_cancelled()
+}
+
+/// 进行中调用的跟踪器
+pub fn in_flight() -> &'static TaskTracker {
+    &IN_FLIGHT
+}
+
+#[cfg(unix)]
+async fn recv_signal() -> &'static str {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut terminate = match signal(SignalKind::terminate()) {
+        Ok(terminate) => terminate,
+        Err(e) => {
+            tracing::error!(error = %e, "Error installing SIGTERM handler");
+            let _ = tokio::signal::ctrl_c().await;
+            return "SIGINT";
+        }
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => "SIGINT",
+        _ = terminate.recv() => "SIGTERM",
+    }
+}
+
+#[cfg(not(unix))]
+async fn recv_signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "Ctrl-C"
+}
+
+/// 监听退出信号：第一次触发优雅关闭，再次收到时立即退出
+pub fn spawn_signal_handler() {
+    tokio::spawn(async {
+        let signal = recv_signal().await;
+        tracing::info!(signal, "Shutdown requested, draining in-flight requests");
+        SHUTDOWN.cancel();
+
+        let signal = recv_signal().await;
+        tracing::warn!(
+            signal,
+            "Second shutdown signal received, exiting immediately"
+        );
+        std::process::exit(130);
+    });
+}
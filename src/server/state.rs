@@ -0,0 +1,116 @@
+use std::{
+    future::Future,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    config::Config,
+    tools::{path_policy::PathPolicy, registry::ToolRegistry},
+};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+tokio::task_local! {
+    /// 正在处理的请求所属路由的状态
+    static CURRENT: Arc<ServerState>;
+}
+
+/// 当前生效的配置、由它构建的路径策略及加载时间
+struct LoadedConfig {
+    config: Arc<Config>,
+    path_policy: Arc<PathPolicy>,
+    loaded_at: DateTime<Utc>,
+}
+
+impl LoadedConfig {
+    fn new(config: Config) -> Self {
+        // 路径策略需要编译 glob 并解析根目录，每个配置版本只构建一次
+        Self {
+            path_policy: Arc::new(PathPolicy::from_config(&config.path_policy)),
+            config: Arc::new(config),
+            loaded_at: Utc::now(),
+        }
+    }
+}
+
+/// 一个 MCP 路由使用的工具注册表与配置
+///
+/// 每次 build_router 或 run 各自持有一份，同一进程内的多个路由互不影响。
+/// 路由通过 [`with_state`] 层把状态放入请求的任务上下文，处理请求时用 [`current`] 读取。
+pub struct ServerState {
+    /// 进程内唯一的编号，用于区分多个路由共用的进程级数据（如结果缓存）
+    pub id: u64,
+    pub registry: ToolRegistry,
+    loaded: RwLock<LoadedConfig>,
+}
+
+impl ServerState {
+    pub fn new(registry: ToolRegistry, config: Config) -> Arc<Self> {
+        Arc::new(Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            registry,
+            loaded: RwLock::new(LoadedConfig::new(config)),
+        })
+    }
+
+    /// 当前生效的配置
+    pub fn config(&self) -> Arc<Config> {
+        self.loaded.read().unwrap().config.clone()
+    }
+
+    /// 当前配置对应的路径策略
+    pub fn path_policy(&self) -> Arc<PathPolicy> {
+        self.loaded.read().unwrap().path_policy.clone()
+    }
+
+    /// 当前配置的加载时间
+    pub fn loaded_at(&self) -> DateTime<Utc> {
+        self.loaded.read().unwrap().loaded_at
+    }
+
+    /// 原子替换配置，返回被替换的旧配置
+    pub fn replace_config(&self, config: Config) -> Arc<Config> {
+        let loaded = LoadedConfig::new(config);
+        std::mem::replace(&mut *self.loaded.write().unwrap(), loaded).config
+    }
+}
+
+/// 当前请求所属路由的状态；只能在路由处理请求期间或 [`scope`] 内调用
+pub fn current() -> Arc<ServerState> {
+    CURRENT
+        .try_with(Arc::clone)
+        .expect("只能在 MCP 路由处理请求期间访问服务状态")
+}
+
+/// 在指定状态下运行 future
+pub async fn scope<F: Future>(state: Arc<ServerState>, future: F) -> F::Output {
+    CURRENT.scope(state, future).await
+}
+
+/// 在当前状态下启动后台任务，任务内同样可以读取配置与工具注册表
+pub fn spawn<F>(future: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(scope(current(), future))
+}
+
+/// 路由最外层的中间件，把路由的状态放入请求的任务上下文
+pub async fn with_state(
+    State(state): State<Arc<ServerState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    scope(state, next.run(request)).await
+}
@@ -0,0 +1,119 @@
+use std::io::IsTerminal;
+
+use tracing_subscriber::{
+    EnvFilter, Layer, Registry, layer::SubscriberExt, util::SubscriberInitExt,
+};
+
+use crate::config::config::{LogFormat, LoggingConfig};
+
+/// 日志级别过滤器：设置了 RUST_LOG 时优先使用环境变量
+fn env_filter(logging: &LoggingConfig) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(&logging.level))
+        .unwrap_or_else(|_| EnvFilter::new("info"))
+}
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// 追踪数据导出器的生命周期守卫，退出前刷新尚未导出的数据
+pub struct TelemetryGuard {
+    #[cfg(feature = "otlp")]
+    tracer_provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.tracer_provider.take()
+            && let Err(e) = provider.shutdown()
+        {
+            eprintln!("Error shutting down OTLP exporter: {}", e);
+        }
+    }
+}
+
+/// 按配置创建 OTLP 导出层
+#[cfg(feature = "otlp")]
+fn otlp_layer(logging: &LoggingConfig) -> (Option<BoxedLayer>, TelemetryGuard) {
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+
+    let Some(endpoint) = &logging.otlp_endpoint else {
+        return (
+            None,
+            TelemetryGuard {
+                tracer_provider: None,
+            },
+        );
+    };
+
+    let exporter = match SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!("Error initializing OTLP exporter: {}", e);
+            return (
+                None,
+                TelemetryGuard {
+                    tracer_provider: None,
+                },
+            );
+        }
+    };
+    let resource = opentelemetry_sdk::Resource::builder()
+        .with_service_name("local_mcp_server")
+        .build();
+    let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build();
+
+    let layer = tracing_opentelemetry::layer()
+        .with_tracer(provider.tracer("local_mcp_server"))
+        .boxed();
+    (
+        Some(layer),
+        TelemetryGuard {
+            tracer_provider: Some(provider),
+        },
+    )
+}
+
+#[cfg(not(feature = "otlp"))]
+fn otlp_layer(logging: &LoggingConfig) -> (Option<BoxedLayer>, TelemetryGuard) {
+    if logging.otlp_endpoint.is_some() {
+        eprintln!(
+            "logging.otlp_endpoint 需要以 otlp 特性编译（cargo build --features otlp），已忽略"
+        );
+    }
+    (None, TelemetryGuard {})
+}
+
+/// 初始化全局日志与链路追踪，返回的守卫需要持有到进程退出
+pub fn init(logging: &LoggingConfig) -> TelemetryGuard {
+    let fmt_layer = match logging.format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer()
+            .with_ansi(std::io::stderr().is_terminal())
+            .with_writer(std::io::stderr)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_writer(std::io::stderr)
+            .boxed(),
+    };
+    let (otlp_layer, guard) = otlp_layer(logging);
+    let mut layers: Vec<BoxedLayer> = vec![fmt_layer];
+    layers.extend(otlp_layer);
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(env_filter(logging))
+        .init();
+
+    guard
+}
@@ -0,0 +1,193 @@
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::{
+    TlsAcceptor,
+    rustls::{
+        RootCertStore, ServerConfig,
+        crypto::ring,
+        pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+        server::WebPkiClientVerifier,
+    },
+    server::TlsStream,
+};
+
+use crate::{config::config::TlsConfig, server::shutdown};
+
+/// 单个连接完成TLS握手的最长时间
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 读取证书、私钥与可选的客户端CA，构建TLS服务端配置
+fn build_server_config(tls: &TlsConfig) -> Result<Arc<ServerConfig>, String> {
+    let certs = CertificateDer::pem_file_iter(&tls.cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("tls.cert_path 读取证书失败 {}: {}", tls.cert_path, e))?;
+    if certs.is_empty() {
+        return Err(format!("tls.cert_path 中没有证书: {}", tls.cert_path));
+    }
+    let key = PrivateKeyDer::from_pem_file(&tls.key_path)
+        .map_err(|e| format!("tls.key_path 读取私钥失败 {}: {}", tls.key_path, e))?;
+
+    let provider = Arc::new(ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS协议配置失败: {}", e))?;
+
+    let builder = match &tls.client_ca_path {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in CertificateDer::pem_file_iter(ca_path)
+                .map_err(|e| format!("tls.client_ca_path 读取CA证书失败 {}: {}", ca_path, e))?
+            {
+                let cert = cert
+                    .map_err(|e| format!("tls.client_ca_path 解析CA证书失败 {}: {}", ca_path, e))?;
+                roots
+                    .add(cert)
+                    .map_err(|e| format!("tls.client_ca_path CA证书无效 {}: {}", ca_path, e))?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| format!("tls.client_ca_path 构建客户端证书校验器失败: {}", e))?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("证书与私钥不匹配: {}", e))?;
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(Arc::new(config))
+}
+
+/// 证书相关文件的修改时间，用于判断是否需要重新加载
+fn files_modified(tls: &TlsConfig) -> Vec<Option<SystemTime>> {
+    [
+        Some(&tls.cert_path),
+        Some(&tls.key_path),
+        tls.client_ca_path.as_ref(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+    .collect()
+}
+
+/// 定期检查证书文件，变化后重新加载；加载失败时继续使用旧证书
+async fn watch_certificates(
+    tls: TlsConfig,
+    acceptor: Arc<RwLock<TlsAcceptor>>,
+    mut last_modified: Vec<Option<SystemTime>>,
+) {
+    let interval = Duration::from_secs(tls.reload_interval_secs.max(1));
+    loop {
+        tokio::time::sleep(interval).await;
+
+        let modified = files_modified(&tls);
+        if modified == last_modified {
+            continue;
+        }
+
+        match build_server_config(&tls) {
+            Ok(server_config) => {
+                *acceptor.write().unwrap() = TlsAcceptor::from(server_config);
+                last_modified = modified;
+                tracing::info!(cert_path = %tls.cert_path, "TLS certificates reloaded");
+            }
+            Err(e) => {
+                // 证书可能正在写入中，下个周期会重试
+                tracing::warn!(error = %e, "Error reloading TLS certificates");
+            }
+        }
+    }
+}
+
+/// TLS监听器：后台接受TCP连接并并发完成握手，供 axum::serve 使用
+pub struct TlsListener {
+    incoming: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+    local_addr: SocketAddr,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, tls: &TlsConfig) -> Result<Self, String> {
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| format!("获取监听地址失败: {}", e))?;
+        let acceptor = Arc::new(RwLock::new(TlsAcceptor::from(build_server_config(tls)?)));
+
+        tokio::spawn(watch_certificates(
+            tls.clone(),
+            acceptor.clone(),
+            files_modified(tls),
+        ));
+
+        let (sender, incoming) = mpsc::channel(64);
+        tokio::spawn(accept_loop(listener, acceptor, sender));
+
+        Ok(Self {
+            incoming,
+            local_addr,
+        })
+    }
+}
+
+async fn accept_loop(
+    listener: TcpListener,
+    acceptor: Arc<RwLock<TlsAcceptor>>,
+    sender: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) {
+    let shutdown = shutdown::token();
+    loop {
+        // 收到关闭信号或监听器已被丢弃时停止接受连接并释放端口
+        let accepted = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = sender.closed() => break,
+            accepted = listener.accept() => accepted,
+        };
+        let (stream, addr) = match accepted {
+            Ok(conn) => conn,
+            Err(_) => {
+                // 文件描述符耗尽等错误时稍作等待，避免空转
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.read().unwrap().clone();
+        let sender = sender.clone();
+        // 握手在独立任务中完成，慢客户端不会阻塞其他连接
+        tokio::spawn(async move {
+            if let Ok(Ok(tls_stream)) =
+                tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+            {
+                let _ = sender.send((tls_stream, addr)).await;
+            }
+        });
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.incoming.recv().await {
+            Some(conn) => conn,
+            // 接收任务在关闭时退出，之后不再产生新连接
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
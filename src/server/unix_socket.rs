@@ -0,0 +1,104 @@
+use std::{
+    fs::{self, DirBuilder, Permissions},
+    io,
+    os::unix::{
+        fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+        net::UnixStream,
+    },
+    path::Path,
+};
+
+use tokio::net::UnixListener;
+
+use crate::config::config::UnixSocketConfig;
+
+/// 绑定Unix域套接字并设置文件权限
+pub fn bind_unix_socket(config: &UnixSocketConfig) -> Result<UnixListener, String> {
+    let mode = config
+        .permissions_mode()
+        .map_err(|e| format!("unix_socket.mode {}", e))?;
+    let path = Path::new(&config.path);
+
+    // 已有进程在该套接字上监听时拒绝启动；只清理上次运行残留、无人监听的套接字文件，其他类型的文件不做处理
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(format!(
+                "unix_socket.path 已存在且不是套接字文件: {}",
+                config.path
+            ));
+        }
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!(
+                "unix_socket.path 已有其他进程在监听: {}",
+                config.path
+            ));
+        }
+        fs::remove_file(path).map_err(|e| format!("删除旧套接字文件失败: {}", e))?;
+    }
+
+    // 先在仅当前用户可访问的临时目录中绑定并设置权限，再移动到目标路径，
+    // 其他用户不会在权限生效之前连接到套接字
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let staging = parent.join(format!(".local_mcp_{}", std::process::id()));
+    DirBuilder::new()
+        .mode(0o700)
+        .create(&staging)
+        .map_err(|e| format!("创建临时目录 {} 失败: {}", staging.display(), e))?;
+    let result = bind_staged(&staging.join("sock"), path, mode);
+    let _ = fs::remove_dir(&staging);
+    result.map_err(|e| format!("绑定Unix套接字 {} 失败: {}", config.path, e))
+}
+
+fn bind_staged(staged: &Path, path: &Path, mode: u32) -> io::Result<UnixListener> {
+    let listener = UnixListener::bind(staged)?;
+    let moved = fs::set_permissions(staged, Permissions::from_mode(mode))
+        .and_then(|_| fs::rename(staged, path));
+    if let Err(e) = moved {
+        let _ = fs::remove_file(staged);
+        return Err(e);
+    }
+    Ok(listener)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn socket_config(name: &str) -> UnixSocketConfig {
+        let path = std::env::temp_dir().join(format!("{}_{}.sock", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        UnixSocketConfig {
+            path: path.to_str().unwrap().to_string(),
+            mode: "640".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn refuses_socket_in_use_and_replaces_stale_one() {
+        let config = socket_config("local_mcp_in_use");
+        let listener = bind_unix_socket(&config).unwrap();
+        let metadata = fs::metadata(&config.path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o640);
+
+        let error = bind_unix_socket(&config).unwrap_err();
+        assert!(error.contains("已有其他进程在监听"), "{}", error);
+        assert!(fs::symlink_metadata(&config.path).is_ok());
+
+        // 监听结束后留下的套接字文件可以被替换
+        drop(listener);
+        let _listener = bind_unix_socket(&config).unwrap();
+        fs::remove_file(&config.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn refuses_regular_file() {
+        let config = socket_config("local_mcp_regular");
+        fs::write(&config.path, b"").unwrap();
+        assert!(bind_unix_socket(&config).is_err());
+        assert!(fs::symlink_metadata(&config.path).unwrap().is_file());
+        fs::remove_file(&config.path).unwrap();
+    }
+}
@@ -0,0 +1,439 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        LazyLock, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{
+    Json,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::{
+    config::config,
+    middleware::auth::{AuthIdentity, extract_token, token_eq},
+    server::shutdown,
+    tools::{error::ToolError, path_policy::PathPolicy, registry::ToolEntry, session},
+};
+
+/// 会话内记住的审批规则在会话状态中的键
+const REMEMBERED_KEY: &str = "approval.remembered";
+
+/// 参数匹配规则：键为参数名，值为支持 * 通配的模式，所有参数都匹配时规则生效
+pub type ArgumentPattern = HashMap<String, String>;
+
+/// 等待审批的调用
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    pub id: u64,
+    pub tool: String,
+    pub arguments: Option<Value>,
+    /// 匹配规则时使用的参数（路径参数为解析后的真实路径），有路径无法解析时为 None
+    pub matched_arguments: Option<Value>,
+    /// 认证身份名称
+    pub client: Option<String>,
+    pub session_id: Option<String>,
+    pub requested_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// 操作员的审批决定
+#[derive(Debug, Deserialize)]
+pub struct ApprovalDecision {
+    pub approved: bool,
+    /// 批准时在本会话内记住的参数规则，之后匹配的调用不再需要审批；空规则匹配该工具的所有调用
+    pub remember: Option<ArgumentPattern>,
+}
+
+struct Pending {
+    request: ApprovalRequest,
+    sender: oneshot::Sender<ApprovalDecision>,
+}
+
+static PENDING: LazyLock<Mutex<BTreeMap<u64, Pending>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 会话内记住的审批
+#[derive(Clone)]
+struct RememberedApproval {
+    tool: String,
+    pattern: ArgumentPattern,
+}
+
+/// 调用结束（包括被取消）时移除待审批项
+struct PendingGuard(u64);
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        PENDING.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// 把模式按未转义的 * 拆成字面片段；"\*" 表示字面 *，"\\" 表示字面 \，其他反斜杠按原样保留
+fn wildcard_parts(pattern: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => parts.push(String::new()),
+            '\\' => {
+                let part = parts.last_mut().unwrap();
+                match chars.next() {
+                    Some(escaped @ ('*' | '\\')) => part.push(escaped),
+                    Some(other) => {
+                        part.push('\\');
+                        part.push(other);
+                    }
+                    None => part.push('\\'),
+                }
+            }
+            other => parts.last_mut().unwrap().push(other),
+        }
+    }
+    parts
+}
+
+/// 以 * 为通配符匹配整段文本
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts = wildcard_parts(pattern);
+    let (first, middle, last) = match parts.as_slice() {
+        [only] => return only == text,
+        [first, middle @ .., last] => (first, middle, last),
+        [] => return false,
+    };
+    let Some(mut rest) = text.strip_prefix(first.as_str()) else {
+        return false;
+    };
+    for part in middle {
+        match rest.find(part.as_str()) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last.as_str())
+}
+
+/// 参数值的文本形式，字符串取原值，其他类型取 JSON 表示
+fn argument_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// 参数是否匹配规则，规则中的参数未传入时不匹配
+pub fn pattern_matches(pattern: &ArgumentPattern, arguments: Option<&Value>) -> bool {
+    pattern.iter().all(|(name, expected)| {
+        arguments
+            .and_then(|arguments| arguments.get(name))
+            .is_some_and(|value| wildcard_match(expected, &argument_text(value)))
+    })
+}
+
+/// 匹配审批规则使用的参数：路径参数替换为按路径策略解析 ".." 与符号链接后的真实路径，
+/// 使指向 "/etc/shadow" 的 "/tmp/x" 不能借 "/tmp/*" 免审；有路径参数无法解析时返回 None
+async fn matched_arguments(entry: &ToolEntry, arguments: Option<&Value>) -> Option<Option<Value>> {
+    let Some(mut matched) = arguments.cloned() else {
+        return Some(None);
+    };
+    let policy = PathPolicy::current();
+    for name in &entry.path_arguments {
+        let Some(value) = matched.get_mut(name.as_str()) else {
+            continue;
+        };
+        let path = value.as_str()?;
+        let resolved = policy.resolve(path).await.ok()?;
+        *value = Value::String(resolved.to_string_lossy().into_owned());
+    }
+    Some(Some(matched))
+}
+
+/// 调用是否需要审批：工具配置了 require_approval，且参数不匹配免审规则与本会话记住的规则；
+/// 路径参数无法解析时规则不生效
+fn needs_approval(tool: &str, matched: Option<Option<&Value>>, session_id: Option<&str>) -> bool {
+    let config = config::current();
+    let Some(tool_config) = config.tools.get(tool) else {
+        return false;
+    };
+    if !tool_config.require_approval {
+        return false;
+    }
+    let Some(arguments) = matched else {
+        return true;
+    };
+    if tool_config
+        .approval_exempt
+        .iter()
+        .any(|pattern| pattern_matches(pattern, arguments))
+    {
+        return false;
+    }
+    let remembered = session_id
+        .and_then(session::get)
+        .and_then(|session| session.get::<Vec<RememberedApproval>>(REMEMBERED_KEY))
+        .unwrap_or_default();
+    !remembered
+        .iter()
+        .any(|approval| approval.tool == tool && pattern_matches(&approval.pattern, arguments))
+}
+
+fn remember(session_id: &str, tool: &str, pattern: ArgumentPattern) {
+    let Some(session) = session::get(session_id) else {
+        return;
+    };
+    let mut remembered = session
+        .get::<Vec<RememberedApproval>>(REMEMBERED_KEY)
+        .unwrap_or_default();
+    remembered.push(RememberedApproval {
+        tool: tool.to_string(),
+        pattern,
+    });
+    session.insert(REMEMBERED_KEY, remembered);
+}
+
+/// 需要审批时挂起调用，直到操作员批准、拒绝或等待超时
+pub async fn check(
+    entry: &ToolEntry,
+    arguments: Option<&Value>,
+    identity: Option<&AuthIdentity>,
+    session_id: Option<&str>,
+) -> Result<(), ToolError> {
+    let tool = entry.tool.name.as_str();
+    if !config::current().tool_requires_approval(tool) {
+        return Ok(());
+    }
+    let matched = matched_arguments(entry, arguments).await;
+    if !needs_approval(tool, matched.as_ref().map(Option::as_ref), session_id) {
+        return Ok(());
+    }
+
+    let timeout = Duration::from_secs(config::current().approval.timeout_secs);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let requested_at = Utc::now();
+    let (sender, receiver) = oneshot::channel();
+    let request = ApprovalRequest {
+        id,
+        tool: tool.to_string(),
+        arguments: arguments.cloned(),
+        matched_arguments: matched.flatten(),
+        client: identity.map(|identity| identity.name.clone()),
+        session_id: session_id.map(str::to_string),
+        requested_at,
+        expires_at: requested_at + timeout,
+    };
+    PENDING
+        .lock()
+        .unwrap()
+        .insert(id, Pending { request, sender });
+    let _pending = PendingGuard(id);
+    tracing::warn!(
+        approval_id = id,
+        tool,
+        "Tool call is waiting for approval at /approvals"
+    );
+
+    let shutdown = shutdown::token();
+    let decision = tokio::select! {
+        _ = shutdown.cancelled() => {
+            return Err(ToolError::Denied("服务正在关闭".to_string()));
+        }
+        decision = tokio::time::timeout(timeout, receiver) => decision,
+    };
+    match decision {
+        Ok(Ok(decision)) if decision.approved => {
+            tracing::info!(approval_id = id, tool, "Tool call approved");
+            if let (Some(session_id), Some(pattern)) = (session_id, decision.remember) {
+                remember(session_id, tool, pattern);
+            }
+            Ok(())
+        }
+        Ok(Ok(_)) => {
+            tracing::info!(approval_id = id, tool, "Tool call denied");
+            Err(ToolError::Denied("操作员拒绝了该调用".to_string()))
+        }
+        Ok(Err(_)) => Err(ToolError::Denied("审批请求已失效".to_string())),
+        Err(_) => {
+            tracing::info!(approval_id = id, tool, "Tool call approval timed out");
+            Err(ToolError::Denied(format!(
+                "等待审批超过 {} 秒",
+                timeout.as_secs()
+            )))
+        }
+    }
+}
+
+/// 审批接口鉴权：请求头必须携带 approval.token，未配置令牌时拒绝所有请求
+fn authorized(headers: &HeaderMap) -> bool {
+    config::current()
+        .approval
+        .token
+        .as_deref()
+        .is_some_and(|expected| {
+            extract_token(headers).is_some_and(|token| token_eq(expected, token))
+        })
+}
+
+fn unauthorized() -> Response {
+    (StatusCode::UNAUTHORIZED, "Approval token required").into_response()
+}
+
+/// 审批页面
+pub async fn handle_approval_page() -> Html<&'static str> {
+    Html(include_str!("approval.html"))
+}
+
+/// 待审批的调用列表
+pub async fn handle_list_approvals(headers: HeaderMap) -> Response {
+    if !authorized(&headers) {
+        return unauthorized();
+    }
+    let pending: Vec<ApprovalRequest> = PENDING
+        .lock()
+        .unwrap()
+        .values()
+        .map(|pending| pending.request.clone())
+        .collect();
+    Json(pending).into_response()
+}
+
+/// 批准或拒绝一个待审批的调用
+pub async fn handle_decide(
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+    Json(decision): Json<ApprovalDecision>,
+) -> Response {
+    if !authorized(&headers) {
+        return unauthorized();
+    }
+    let Some(pending) = PENDING.lock().unwrap().remove(&id) else {
+        return (StatusCode::NOT_FOUND, "Approval request not found").into_response();
+    };
+    let _ = pending.sender.send(decision);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn pattern(rules: &[(&str, &str)]) -> ArgumentPattern {
+        rules
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn wildcard_matches_whole_text() {
+        assert!(wildcard_match("/tmp/a.txt", "/tmp/a.txt"));
+        assert!(!wildcard_match("/tmp/a.txt", "/tmp/a.txt.bak"));
+        assert!(wildcard_match("/tmp/*", "/tmp/a/b.txt"));
+        assert!(wildcard_match("/tmp/*", "/tmp/"));
+        assert!(!wildcard_match("/tmp/*", "/var/tmp/a"));
+        assert!(wildcard_match("*.log", "/var/log/app.log"));
+        assert!(wildcard_match("/home/*/notes/*.md", "/home/me/notes/a.md"));
+        assert!(!wildcard_match(
+            "/home/*/notes/*.md",
+            "/home/me/notes/a.txt"
+        ));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[test]
+    fn wildcard_does_not_reuse_matched_text() {
+        assert!(!wildcard_match("a*a", "a"));
+        assert!(wildcard_match("a*a", "aa"));
+        assert!(!wildcard_match("a*bc*c", "abc"));
+        assert!(wildcard_match("a*bc*c", "abcc"));
+    }
+
+    #[test]
+    fn escaped_star_is_literal() {
+        assert!(wildcard_match(r"/tmp/\*", "/tmp/*"));
+        assert!(!wildcard_match(r"/tmp/\*", "/tmp/a"));
+        assert!(wildcard_match(r"a\\*", r"a\b"));
+        assert!(!wildcard_match(r"a\\*", "ab"));
+        assert!(wildcard_match(r"C:\Users\me", r"C:\Users\me"));
+    }
+
+    #[test]
+    fn all_rule_arguments_must_match() {
+        let rule = pattern(&[("host", "*.example.com"), ("count", "3")]);
+        assert!(pattern_matches(
+            &rule,
+            Some(&json!({"host": "a.example.com", "count": 3, "extra": true}))
+        ));
+        assert!(!pattern_matches(
+            &rule,
+            Some(&json!({"host": "a.example.com", "count": 4}))
+        ));
+        assert!(!pattern_matches(
+            &rule,
+            Some(&json!({"host": "a.example.com"}))
+        ));
+        assert!(!pattern_matches(&rule, None));
+        assert!(pattern_matches(&pattern(&[]), None));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn path_arguments_match_resolved_path() {
+        use crate::{
+            server::state::{self, ServerState},
+            tools::{
+                public::system::file::read_file_tool::handle_read_file_tool_entry,
+                registry::ToolRegistry,
+            },
+        };
+
+        let base = std::env::temp_dir().join(format!("local_mcp_approval_{}", std::process::id()));
+        let exempt = base.join("exempt");
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&exempt).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(exempt.join("a.txt"), "a").unwrap();
+        std::fs::write(outside.join("secret"), "s").unwrap();
+        std::os::unix::fs::symlink(outside.join("secret"), exempt.join("link")).unwrap();
+        let base = std::fs::canonicalize(&base).unwrap();
+
+        let entry = handle_read_file_tool_entry();
+        let rule = pattern(&[("file_path", &format!("{}/exempt/*", base.display()))]);
+        let matches = |path: std::path::PathBuf| {
+            let entry = entry.clone();
+            let rule = rule.clone();
+            async move {
+                let arguments = json!({"file_path": path});
+                matched_arguments(&entry, Some(&arguments))
+                    .await
+                    .map(|matched| pattern_matches(&rule, matched.as_ref()))
+            }
+        };
+        let state = ServerState::new(
+            ToolRegistry::new(Vec::new()),
+            serde_yaml::from_str("listen_port: 3000").unwrap(),
+        );
+        state::scope(state, async {
+            assert_eq!(matches(exempt.join("a.txt")).await, Some(true));
+            assert_eq!(matches(exempt.join("../exempt/a.txt")).await, Some(true));
+            assert_eq!(matches(exempt.join("link")).await, Some(false));
+            assert_eq!(matches(exempt.join("../outside/secret")).await, Some(false));
+            // 无法解析的路径不能匹配任何规则
+            assert_eq!(matches(exempt.join("missing")).await, None);
+        })
+        .await;
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
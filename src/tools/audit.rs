@@ -0,0 +1,207 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, mpsc},
+    time::Duration,
+};
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    config::config::{self, AuditConfig},
+    middleware::auth::AuthIdentity,
+    tools::{
+        handler,
+        tool_dto::{JsonRpcError, JsonRpcResponse, ToolCallResult},
+    },
+};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// 一次工具调用的审计记录
+#[derive(Debug, Serialize)]
+struct AuditEntry {
+    timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    tool: String,
+    arguments: Option<Value>,
+    outcome: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_code: Option<i32>,
+    duration_ms: u128,
+    result_bytes: usize,
+}
+
+/// 发往后台写入线程的消息
+enum AuditMessage {
+    /// 记录及写入时使用的审计配置
+    Entry(AuditEntry, Arc<AuditConfig>),
+    /// 写完之前的记录后回复
+    Flush(mpsc::Sender<()>),
+}
+
+/// 审计记录由后台线程写入，调用路径上不做文件IO
+static WRITER: LazyLock<mpsc::Sender<AuditMessage>> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || write_loop(receiver));
+    sender
+});
+
+/// 按配置把参数中需要脱敏的字段替换为占位符
+fn redact_arguments(audit: &AuditConfig, tool: &str, arguments: Option<Value>) -> Option<Value> {
+    match arguments {
+        Some(Value::Object(mut arguments)) => {
+            for (name, value) in arguments.iter_mut() {
+                if audit.should_redact(tool, name) {
+                    *value = Value::String(REDACTED.to_string());
+                }
+            }
+            Some(Value::Object(arguments))
+        }
+        other => other,
+    }
+}
+
+/// 记录一次 tools/call，未配置审计日志时不做任何事
+pub fn record_tool_call(
+    identity: Option<&AuthIdentity>,
+    session_id: Option<&str>,
+    tool: &str,
+    arguments: Option<Value>,
+    result: &Result<JsonRpcResponse<ToolCallResult>, JsonRpcError>,
+    duration: Duration,
+) {
+    let config = config::current();
+    let Some(audit) = &config.audit else {
+        return;
+    };
+
+    let (error_code, result_bytes) = match result {
+        Ok(response) => (
+            None,
+            serde_json::to_vec(&response.result).map_or(0, |bytes| bytes.len()),
+        ),
+        Err(error) => (Some(error.error.code), 0),
+    };
+
+    let entry = AuditEntry {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        client: identity.map(|identity| identity.name.clone()),
+        session_id: session_id.map(str::to_string),
+        tool: tool.to_string(),
+        arguments: redact_arguments(audit, tool, arguments),
+        outcome: handler::call_outcome(result),
+        error_code,
+        duration_ms: duration.as_millis(),
+        result_bytes,
+    };
+    let _ = WRITER.send(AuditMessage::Entry(entry, Arc::new(audit.clone())));
+}
+
+/// 等待已提交的审计记录写入文件，退出前调用
+pub fn flush(timeout: Duration) {
+    let (done, wait) = mpsc::channel();
+    if WRITER.send(AuditMessage::Flush(done)).is_ok() {
+        let _ = wait.recv_timeout(timeout);
+    }
+}
+
+/// 当前打开的审计日志文件
+struct AuditFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    size: u64,
+}
+
+impl AuditFile {
+    fn open(path: &Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            size,
+        })
+    }
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// 轮转日志：<path>.N-1 -> <path>.N ... <path> -> <path>.1，超出 max_files 的文件被删除
+fn rotate(path: &Path, max_files: usize) -> std::io::Result<()> {
+    if max_files == 0 {
+        return fs::remove_file(path);
+    }
+    let _ = fs::remove_file(rotated_path(path, max_files));
+    for index in (1..max_files).rev() {
+        let from = rotated_path(path, index);
+        if from.exists() {
+            fs::rename(&from, rotated_path(path, index + 1))?;
+        }
+    }
+    fs::rename(path, rotated_path(path, 1))
+}
+
+fn write_entry(
+    current: &mut Option<AuditFile>,
+    audit: &AuditConfig,
+    line: &[u8],
+) -> std::io::Result<()> {
+    let path = Path::new(&audit.path);
+    // 配置热加载修改路径后切换到新文件
+    if current.as_ref().is_none_or(|file| file.path != path) {
+        *current = Some(AuditFile::open(path)?);
+    }
+    let Some(file) = current.as_mut() else {
+        unreachable!()
+    };
+
+    let max_size = audit.max_size_mb * 1024 * 1024;
+    if file.size > 0 && file.size + line.len() as u64 > max_size {
+        file.writer.flush()?;
+        *current = None;
+        rotate(path, audit.max_files)?;
+        *current = Some(AuditFile::open(path)?);
+        return write_entry(current, audit, line);
+    }
+
+    file.writer.write_all(line)?;
+    file.writer.flush()?;
+    file.size += line.len() as u64;
+    Ok(())
+}
+
+fn write_loop(receiver: mpsc::Receiver<AuditMessage>) {
+    let mut current: Option<AuditFile> = None;
+    while let Ok(message) = receiver.recv() {
+        let (entry, audit) = match message {
+            AuditMessage::Entry(entry, audit) => (entry, audit),
+            AuditMessage::Flush(done) => {
+                let _ = done.send(());
+                continue;
+            }
+        };
+
+        let mut line = serde_json::to_vec(&entry).unwrap_or_default();
+        line.push(b'\n');
+        if let Err(e) = write_entry(&mut current, &audit, &line) {
+            tracing::error!(path = %audit.path, error = %e, "Error writing audit log");
+            current = None;
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::{middleware::auth::AuthIdentity, tools::tool_dto::ToolCallResult};
+
+/// 缓存条目数量上限
+const MAX_ENTRIES: usize = 10_000;
+/// 缓存结果的总大小上限（按序列化后的字节数计算）
+const MAX_TOTAL_BYTES: usize = 64 * 1024 * 1024;
+/// 单条结果的大小上限，超过时不缓存，避免少数大结果挤掉其他条目
+const MAX_ENTRY_BYTES: usize = 4 * 1024 * 1024;
+/// 清理时使用的最长存活时间，超过该时间的条目无论配置如何都视为过期
+const MAX_ENTRY_AGE: Duration = Duration::from_secs(3600);
+
+struct CachedResult {
+    result: ToolCallResult,
+    stored: Instant,
+    size: usize,
+}
+
+/// 按条目数与总字节数限制大小的结果缓存，超出时先清理过期条目，再淘汰最早写入的条目
+struct ResultCache {
+    entries: HashMap<String, CachedResult>,
+    total_bytes: usize,
+    max_entries: usize,
+    max_total_bytes: usize,
+    max_entry_bytes: usize,
+}
+
+impl ResultCache {
+    fn new(max_entries: usize, max_total_bytes: usize, max_entry_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            total_bytes: 0,
+            max_entries,
+            max_total_bytes,
+            max_entry_bytes,
+        }
+    }
+
+    fn get(&mut self, key: &str, ttl: Duration) -> Option<(ToolCallResult, Duration)> {
+        let age = self.entries.get(key)?.stored.elapsed();
+        if age >= ttl {
+            self.remove(key);
+            return None;
+        }
+        self.entries
+            .get(key)
+            .map(|cached| (cached.result.clone(), age))
+    }
+
+    fn put(&mut self, key: String, result: &ToolCallResult) {
+        let size = serde_json::to_vec(result).map_or(usize::MAX, |bytes| bytes.len());
+        self.remove(&key);
+        if size > self.max_entry_bytes {
+            return;
+        }
+        self.make_room(size);
+        self.total_bytes += size;
+        self.entries.insert(
+            key,
+            CachedResult {
+                result: result.clone(),
+                stored: Instant::now(),
+                size,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(cached) = self.entries.remove(key) {
+            self.total_bytes -= cached.size;
+        }
+    }
+
+    fn is_full(&self, incoming: usize) -> bool {
+        self.entries.len() >= self.max_entries || self.total_bytes + incoming > self.max_total_bytes
+    }
+
+    /// 为即将写入的结果腾出空间
+    fn make_room(&mut self, incoming: usize) {
+        if !self.is_full(incoming) {
+            return;
+        }
+        self.entries.retain(|_, cached| {
+            let keep = cached.stored.elapsed() < MAX_ENTRY_AGE;
+            if !keep {
+                self.total_bytes -= cached.size;
+            }
+            keep
+        });
+        if !self.is_full(incoming) {
+            return;
+        }
+        let mut oldest: Vec<(Instant, String)> = self
+            .entries
+            .iter()
+            .map(|(key, cached)| (cached.stored, key.clone()))
+            .collect();
+        oldest.sort_unstable();
+        for (_, key) in oldest {
+            if !self.is_full(incoming) {
+                break;
+            }
+            self.remove(&key);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.total_bytes = 0;
+    }
+}
+
+/// 以 "工具名 + 规范化参数" 为键的调用结果缓存
+static CACHE: LazyLock<Mutex<ResultCache>> = LazyLock::new(|| {
+    Mutex::new(ResultCache::new(
+        MAX_ENTRIES,
+        MAX_TOTAL_BYTES,
+        MAX_ENTRY_BYTES,
+    ))
+});
+
+/// 缓存键：按路由与调用方身份隔离，不同路由或不同令牌（可访问的路径与工具可能不同）不共用结果；
+/// 参数对象按键名排序后序列化，参数顺序不同的调用共用同一条缓存
+pub fn cache_key(
+    state_id: u64,
+    identity: Option<&AuthIdentity>,
+    tool: &str,
+    arguments: Option<&Value>,
+) -> String {
+    let arguments = arguments
+        .filter(|args| !args.is_null())
+        .map(canonicalize)
+        .unwrap_or_else(|| Value::Object(Default::default()));
+    let client = match identity {
+        Some(identity) => format!("identity:{}", identity.name),
+        None => "anonymous".to_string(),
+    };
+    format!("{}\n{}\n{}\n{}", state_id, client, tool, arguments)
+}
+
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), canonicalize(v)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        other => other.clone(),
+    }
+}
+
+/// 查询未过期的缓存结果，同时返回结果已缓存的时间；按当前配置的 ttl 判断是否过期
+pub fn get(key: &str, ttl: Duration) -> Option<(ToolCallResult, Duration)> {
+    CACHE.lock().unwrap().get(key, ttl)
+}
+
+/// 写入调用结果，失败的结果与超过单条大小上限的结果不缓存
+pub fn put(key: String, result: &ToolCallResult) {
+    if result.is_error == Some(true) {
+        return;
+    }
+    CACHE.lock().unwrap().put(key, result);
+}
+
+/// 清空缓存，配置重新加载后调用，避免返回按旧配置（如参数默认值、访问范围）得到的结果
+pub fn clear() {
+    CACHE.lock().unwrap().clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::tools::tool_dto::ToolContent;
+
+    fn text_result(text: &str) -> ToolCallResult {
+        ToolCallResult {
+            content: vec![ToolContent {
+                content_type: "text".to_string(),
+                text: Some(text.to_string()),
+                data: None,
+                mime_type: None,
+                annotations: None,
+                resource: None,
+            }],
+            is_error: Some(false),
+            meta: None,
+            structured_content: None,
+        }
+    }
+
+    fn size_of(result: &ToolCallResult) -> usize {
+        serde_json::to_vec(result).unwrap().len()
+    }
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn cache_key_ignores_argument_order() {
+        let a = json!({"path": "/tmp", "options": {"hidden": true, "depth": 2}});
+        let b = json!({"options": {"depth": 2, "hidden": true}, "path": "/tmp"});
+        assert_eq!(
+            cache_key(1, None, "list files", Some(&a)),
+            cache_key(1, None, "list files", Some(&b))
+        );
+    }
+
+    #[test]
+    fn cache_key_keeps_array_order_and_values() {
+        let a = json!({"hosts": ["a", "b"]});
+        let b = json!({"hosts": ["b", "a"]});
+        assert_ne!(
+            cache_key(1, None, "ping", Some(&a)),
+            cache_key(1, None, "ping", Some(&b))
+        );
+        assert_ne!(
+            cache_key(1, None, "ping", Some(&json!({"count": 1}))),
+            cache_key(1, None, "ping", Some(&json!({"count": "1"})))
+        );
+    }
+
+    #[test]
+    fn cache_key_treats_missing_null_and_empty_arguments_alike() {
+        let empty = cache_key(1, None, "read ip", None);
+        assert_eq!(empty, cache_key(1, None, "read ip", Some(&Value::Null)));
+        assert_eq!(empty, cache_key(1, None, "read ip", Some(&json!({}))));
+        assert_ne!(empty, cache_key(1, None, "cat file", None));
+    }
+
+    #[test]
+    fn cache_key_is_scoped_by_router_and_identity() {
+        let identity = |name: &str| AuthIdentity {
+            name: name.to_string(),
+            allowed_tools: None,
+        };
+        let arguments = json!({"file_path": "/tmp/a"});
+        let key = |state_id, identity: Option<&AuthIdentity>| {
+            cache_key(state_id, identity, "cat file", Some(&arguments))
+        };
+        let (a, same, b) = (identity("a"), identity("a"), identity("b"));
+        assert_eq!(key(1, Some(&a)), key(1, Some(&same)));
+        assert_ne!(key(1, Some(&a)), key(1, Some(&b)));
+        assert_ne!(key(1, Some(&a)), key(1, None));
+        assert_ne!(key(1, Some(&a)), key(2, Some(&a)));
+    }
+
+    #[test]
+    fn expired_entries_are_not_returned() {
+        let mut cache = ResultCache::new(10, 1 << 20, 1 << 20);
+        cache.put("key".to_string(), &text_result("cached"));
+        assert!(cache.get("key", TTL).is_some());
+        assert!(cache.get("key", Duration::ZERO).is_none());
+        assert_eq!(cache.total_bytes, 0);
+        assert!(cache.get("key", TTL).is_none());
+    }
+
+    #[test]
+    fn replacing_an_entry_keeps_byte_count_accurate() {
+        let mut cache = ResultCache::new(10, 1 << 20, 1 << 20);
+        let short = text_result("a");
+        let long = text_result(&"b".repeat(100));
+        cache.put("key".to_string(), &short);
+        cache.put("key".to_string(), &long);
+        assert_eq!(cache.total_bytes, size_of(&long));
+        cache.clear();
+        assert_eq!(cache.total_bytes, 0);
+        assert!(cache.get("key", TTL).is_none());
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_when_bytes_exceed_limit() {
+        let result = text_result(&"x".repeat(100));
+        let size = size_of(&result);
+        let mut cache = ResultCache::new(100, size * 3, size);
+        for key in ["a", "b", "c", "d"] {
+            cache.put(key.to_string(), &result);
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert!(cache.get("a", TTL).is_none());
+        for key in ["b", "c", "d"] {
+            assert!(cache.get(key, TTL).is_some(), "{} evicted", key);
+        }
+        assert!(cache.total_bytes <= size * 3);
+    }
+
+    #[test]
+    fn oldest_entries_are_evicted_when_count_exceeds_limit() {
+        let mut cache = ResultCache::new(2, 1 << 20, 1 << 20);
+        for key in ["a", "b", "c"] {
+            cache.put(key.to_string(), &text_result(key));
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get("a", TTL).is_none());
+        assert!(cache.get("c", TTL).is_some());
+    }
+
+    #[test]
+    fn oversized_results_are_not_cached() {
+        let small = text_result("small");
+        let large = text_result(&"x".repeat(1000));
+        let mut cache = ResultCache::new(10, 1 << 20, size_of(&small));
+        cache.put("small".to_string(), &small);
+        cache.put("large".to_string(), &large);
+        assert!(cache.get("small", TTL).is_some());
+        assert!(cache.get("large", TTL).is_none());
+        assert_eq!(cache.total_bytes, size_of(&small));
+    }
+}
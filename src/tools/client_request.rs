@@ -0,0 +1,230 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use axum::extract::ws::Message;
+use serde_json::Value;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::tools::error::ToolError;
+
+/// 会话状态中保存连接请求通道的键
+pub const CLIENT_CHANNEL_KEY: &str = "client_request.channel";
+
+/// 客户端对服务端请求的回复：result 或 error 对象
+type Reply = Result<Value, Value>;
+
+/// 服务端经 WebSocket 连接向客户端发起请求（如 sampling/createMessage、roots/list），
+/// 按请求ID把客户端发回的响应交给等待的调用
+pub struct ClientChannel {
+    outbound: mpsc::Sender<Message>,
+    /// 等待响应的请求，连接关闭后为 None
+    pending: Mutex<Option<HashMap<String, oneshot::Sender<Reply>>>>,
+    next_id: AtomicU64,
+}
+
+/// 请求结束（包括调用被取消）时移除等待项
+struct PendingGuard<'a> {
+    channel: &'a ClientChannel,
+    id: String,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(pending) = self.channel.pending.lock().unwrap().as_mut() {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+impl ClientChannel {
+    pub fn new(outbound: mpsc::Sender<Message>) -> Arc<Self> {
+        Arc::new(Self {
+            outbound,
+            pending: Mutex::new(Some(HashMap::new())),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// 发送请求并等待客户端响应；客户端返回错误或连接关闭时返回 ToolError::Failed
+    pub async fn request(&self, method: &str, params: Option<Value>) -> Result<Value, ToolError> {
+        // 使用字符串ID，避免与客户端自己的数字请求ID混淆
+        let id = format!("server-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let closed = || ToolError::Failed("客户端连接已关闭".to_string());
+        let (sender, receiver) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .as_mut()
+            .ok_or_else(closed)?
+            .insert(id.clone(), sender);
+        let _pending = PendingGuard {
+            channel: self,
+            id: id.clone(),
+        };
+
+        let mut request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+        });
+        if let Some(params) = params {
+            request["params"] = params;
+        }
+        self.outbound
+            .send(Message::Text(request.to_string().into()))
+            .await
+            .map_err(|_| closed())?;
+
+        match receiver.await.map_err(|_| closed())? {
+            Ok(result) => Ok(result),
+            Err(error) => Err(ToolError::Failed(format!(
+                "客户端拒绝了 {} 请求: {}",
+                method,
+                error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+            ))),
+        }
+    }
+
+    /// 把客户端发来的 JSON-RPC 响应交给对应的请求；没有等待该ID的请求时返回 false
+    pub fn resolve(&self, response: &Value) -> bool {
+        let Some(id) = response.get("id").and_then(Value::as_str) else {
+            return false;
+        };
+        let Some(sender) = self
+            .pending
+            .lock()
+            .unwrap()
+            .as_mut()
+            .and_then(|pending| pending.remove(id))
+        else {
+            return false;
+        };
+        let reply = match response.get("error") {
+            Some(error) => Err(error.clone()),
+            None => Ok(response.get("result").cloned().unwrap_or(Value::Null)),
+        };
+        let _ = sender.send(reply);
+        true
+    }
+
+    /// 连接关闭时让等待中的请求立即失败，之后的请求直接返回错误
+    pub fn close(&self) {
+        self.pending.lock().unwrap().take();
+    }
+}
+
+/// 消息是否为 JSON-RPC 响应（没有 method，带有 result 或 error）
+pub fn is_response(message: &Value) -> bool {
+    message.get("method").is_none()
+        && (message.get("result").is_some() || message.get("error").is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// 读取发往客户端的请求
+    async fn sent_request(outbound: &mut mpsc::Receiver<Message>) -> Value {
+        match outbound.recv().await {
+            Some(Message::Text(text)) => serde_json::from_str(text.as_str()).unwrap(),
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn responses_are_routed_by_id() {
+        let (sender, mut outbound) = mpsc::channel(8);
+        let channel = ClientChannel::new(sender);
+
+        let first = tokio::spawn({
+            let channel = channel.clone();
+            async move { channel.request("roots/list", None).await }
+        });
+        let first_request = sent_request(&mut outbound).await;
+        let second = tokio::spawn({
+            let channel = channel.clone();
+            async move {
+                channel
+                    .request("sampling/createMessage", Some(json!({"maxTokens": 8})))
+                    .await
+            }
+        });
+        let second_request = sent_request(&mut outbound).await;
+        assert_eq!(second_request["method"], "sampling/createMessage");
+        assert_eq!(second_request["params"]["maxTokens"], 8);
+        assert_ne!(first_request["id"], second_request["id"]);
+
+        // 响应顺序与请求顺序无关
+        assert!(channel.resolve(&json!({
+            "jsonrpc": "2.0", "id": second_request["id"],
+            "error": {"code": -32601, "message": "sampling not supported"}
+        })));
+        assert!(channel.resolve(&json!({
+            "jsonrpc": "2.0", "id": first_request["id"], "result": {"roots": []}
+        })));
+        assert_eq!(first.await.unwrap().unwrap(), json!({"roots": []}));
+        let error = second.await.unwrap().unwrap_err();
+        assert!(
+            error.to_string().contains("sampling not supported"),
+            "{}",
+            error
+        );
+
+        // 已完成或未知的ID不再匹配
+        assert!(!channel.resolve(&json!({"id": first_request["id"], "result": {}})));
+        assert!(!channel.resolve(&json!({"id": 1, "result": {}})));
+        assert!(channel.pending.lock().unwrap().as_ref().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancelled_request_is_forgotten() {
+        let (sender, mut outbound) = mpsc::channel(8);
+        let channel = ClientChannel::new(sender);
+        let request = tokio::spawn({
+            let channel = channel.clone();
+            async move { channel.request("roots/list", None).await }
+        });
+        let id = sent_request(&mut outbound).await["id"].clone();
+        request.abort();
+        let _ = request.await;
+        assert!(!channel.resolve(&json!({"id": id, "result": {}})));
+    }
+
+    #[tokio::test]
+    async fn close_fails_waiting_requests() {
+        let (sender, mut outbound) = mpsc::channel(8);
+        let channel = ClientChannel::new(sender);
+        let request = tokio::spawn({
+            let channel = channel.clone();
+            async move { channel.request("roots/list", None).await }
+        });
+        sent_request(&mut outbound).await;
+        channel.close();
+        assert!(matches!(request.await.unwrap(), Err(ToolError::Failed(_))));
+        assert!(matches!(
+            channel.request("roots/list", None).await,
+            Err(ToolError::Failed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn closed_connection_fails_request() {
+        let (sender, outbound) = mpsc::channel(8);
+        drop(outbound);
+        let error = ClientChannel::new(sender)
+            .request("roots/list", None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ToolError::Failed(_)));
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// 工具的并发信号量及其对应的并发上限
+struct ToolSemaphore {
+    max: usize,
+    semaphore: Arc<Semaphore>,
+}
+
+impl ToolSemaphore {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            semaphore: Arc::new(Semaphore::new(max)),
+        }
+    }
+}
+
+static SEMAPHORES: LazyLock<Mutex<HashMap<String, ToolSemaphore>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 获取工具的并发许可，许可释放前同一工具最多执行 max 个调用
+pub async fn acquire(tool: &str, max: usize) -> OwnedSemaphorePermit {
+    let semaphore = {
+        let mut semaphores = SEMAPHORES.lock().unwrap();
+        let entry = semaphores
+            .entry(tool.to_string())
+            .or_insert_with(|| ToolSemaphore::new(max));
+        // 配置热加载修改上限后换用新信号量，旧许可在调用结束后自然释放
+        if entry.max != max {
+            *entry = ToolSemaphore::new(max);
+        }
+        entry.semaphore.clone()
+    };
+
+    semaphore
+        .acquire_owned()
+        .await
+        .expect("工具并发信号量不会被关闭")
+}
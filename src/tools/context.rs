@@ -0,0 +1,220 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use crate::tools::{
+    client_request::{CLIENT_CHANNEL_KEY, ClientChannel},
+    error::ToolError,
+    notifier,
+    session::{self, ClientInfo, SessionState},
+};
+
+/// 工具调用上下文：会话信息、会话内的键值存储、取消信号与进度上报
+#[derive(Clone, Default)]
+pub struct ToolContext {
+    session_id: Option<String>,
+    session: Option<Arc<SessionState>>,
+    cancellation: CancellationToken,
+    progress: ProgressReporter,
+}
+
+impl ToolContext {
+    /// 创建调用上下文；progress_token 为请求 _meta.progressToken。
+    /// 不是服务端分配的会话ID按无会话处理
+    pub fn new(session_id: Option<&str>, progress_token: Option<Value>) -> Self {
+        let session = session_id.and_then(session::get);
+        let session_id = session_id.filter(|_| session.is_some()).map(str::to_string);
+        Self {
+            session_id: session_id.clone(),
+            session,
+            cancellation: CancellationToken::new(),
+            progress: ProgressReporter {
+                session_id,
+                token: progress_token,
+            },
+        }
+    }
+
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// 客户端在 initialize 中提供的信息
+    pub fn client_info(&self) -> Option<ClientInfo> {
+        self.session.as_ref().and_then(|s| s.client_info())
+    }
+
+    /// 读取会话内保存的值，没有会话或类型不一致时返回 None
+    pub fn get<T: Clone + 'static>(&self, key: &str) -> Option<T> {
+        self.session.as_ref().and_then(|s| s.get(key))
+    }
+
+    /// 在会话内保存值，同一会话的后续调用可以读取；没有会话时不保存
+    pub fn insert<T: Send + Sync + 'static>(&self, key: &str, value: T) {
+        if let Some(session) = &self.session {
+            session.insert(key, value);
+        }
+    }
+
+    pub fn remove(&self, key: &str) {
+        if let Some(session) = &self.session {
+            session.remove(key);
+        }
+    }
+
+    /// 调用被客户端取消或超时后触发
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    pub fn progress(&self) -> &ProgressReporter {
+        &self.progress
+    }
+
+    /// 向客户端发起请求并等待响应（如 sampling/createMessage、roots/list），调用被取消时放弃等待；
+    /// 只有 WebSocket 会话可以接收服务端请求
+    pub async fn request(&self, method: &str, params: Option<Value>) -> Result<Value, ToolError> {
+        let channel = self
+            .get::<Arc<ClientChannel>>(CLIENT_CHANNEL_KEY)
+            .ok_or_else(|| {
+                ToolError::Failed(
+                    "当前连接不支持服务端发起请求，需要使用 WebSocket 连接".to_string(),
+                )
+            })?;
+        tokio::select! {
+            _ = self.cancellation.cancelled() => Err(ToolError::Cancelled),
+            result = channel.request(method, params) => result,
+        }
+    }
+}
+
+/// 向发起调用的会话发送 notifications/progress；请求未携带 progressToken 时不发送
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    session_id: Option<String>,
+    token: Option<Value>,
+}
+
+impl ProgressReporter {
+    pub fn report(&self, progress: f64, total: Option<f64>, message: Option<&str>) {
+        let (Some(session_id), Some(token)) = (&self.session_id, &self.token) else {
+            return;
+        };
+        let mut params = serde_json::json!({
+            "progressToken": token,
+            "progress": progress,
+        });
+        if let Some(total) = total {
+            params["total"] = total.into();
+        }
+        if let Some(message) = message {
+            params["message"] = message.into();
+        }
+        notifier::notify_session(session_id, "notifications/progress", Some(params));
+    }
+}
+
+/// 进行中调用的取消信号，按 (会话ID, 请求ID) 索引
+static CANCELLATIONS: LazyLock<Mutex<HashMap<(String, String), CancellationToken>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 登记可被 notifications/cancelled 取消的调用，返回值释放时取消登记；
+/// 同一会话中相同 request id 的调用仍在进行时返回 None
+pub fn register_call(
+    session_id: &str,
+    request_id: &Value,
+    token: CancellationToken,
+) -> Option<CallRegistration> {
+    let key = (session_id.to_string(), request_id.to_string());
+    match CANCELLATIONS.lock().unwrap().entry(key.clone()) {
+        // 同一会话中该 request id 的调用仍在进行，不能覆盖它的取消信号
+        Entry::Occupied(_) => None,
+        Entry::Vacant(entry) => {
+            entry.insert(token);
+            Some(CallRegistration { key })
+        }
+    }
+}
+
+pub struct CallRegistration {
+    key: (String, String),
+}
+
+impl Drop for CallRegistration {
+    fn drop(&mut self) {
+        CANCELLATIONS.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// 处理客户端的 notifications/cancelled，只能取消同一会话发起的调用
+pub fn cancel_call(session_id: &str, request_id: &Value) {
+    let key = (session_id.to_string(), request_id.to_string());
+    if let Some(token) = CANCELLATIONS.lock().unwrap().get(&key) {
+        token.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_session_id_is_treated_as_no_session() {
+        let ctx = ToolContext::new(Some("forged"), None);
+        assert_eq!(ctx.session_id(), None);
+        ctx.insert("cwd", "/tmp".to_string());
+        assert_eq!(ctx.get::<String>("cwd"), None);
+        assert!(session::get("forged").is_none());
+    }
+
+    #[test]
+    fn issued_session_is_shared_between_calls() {
+        let session_id = session::create(None).unwrap();
+        let first = ToolContext::new(Some(&session_id), None);
+        assert_eq!(first.session_id(), Some(session_id.as_str()));
+        first.insert("count", 1u32);
+
+        let second = ToolContext::new(Some(&session_id), None);
+        assert_eq!(second.get::<u32>("count"), Some(1));
+        session::remove_session(&session_id);
+    }
+
+    #[test]
+    fn cancel_call_only_reaches_same_session() {
+        let token = CancellationToken::new();
+        let request_id = Value::from(7);
+        let registration = register_call("session-a", &request_id, token.clone()).unwrap();
+
+        cancel_call("session-b", &request_id);
+        assert!(!token.is_cancelled());
+        cancel_call("session-a", &request_id);
+        assert!(token.is_cancelled());
+
+        drop(registration);
+        let key = ("session-a".to_string(), request_id.to_string());
+        assert!(!CANCELLATIONS.lock().unwrap().contains_key(&key));
+    }
+
+    #[test]
+    fn duplicate_request_id_does_not_replace_in_flight_call() {
+        let first = CancellationToken::new();
+        let request_id = Value::from("dup");
+        let registration = register_call("session-dup", &request_id, first.clone()).unwrap();
+        assert!(register_call("session-dup", &request_id, CancellationToken::new()).is_none());
+
+        cancel_call("session-dup", &request_id);
+        assert!(first.is_cancelled());
+
+        // 第一个调用结束后可以再次使用该 request id
+        drop(registration);
+        assert!(register_call("session-dup", &request_id, CancellationToken::new()).is_some());
+    }
+}
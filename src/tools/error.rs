@@ -0,0 +1,110 @@
+use std::{any::Any, fmt, io};
+
+use crate::tools::tool_dto::{ToolCallResult, ToolContent};
+
+/// 工具执行错误，转换为 isError 结果返回给客户端
+#[derive(Debug)]
+pub enum ToolError {
+    /// 参数无效
+    InvalidArguments(String),
+    /// 文件、网络等 IO 操作失败
+    Io(io::Error),
+    /// 其他执行失败
+    Failed(String),
+    /// 工具处理函数发生 panic
+    Panicked { tool: String, message: String },
+    /// 调用被客户端取消
+    Cancelled,
+    /// 需要人工审批的调用被拒绝或等待超时
+    Denied(String),
+    /// 路径不在允许的目录内或匹配禁止规则
+    AccessDenied { path: String, reason: String },
+}
+
+impl ToolError {
+    /// 结构化结果中的错误类型
+    pub fn code(&self) -> &'static str {
+        match self {
+            ToolError::InvalidArguments(_) => "invalid_arguments",
+            ToolError::Io(_) => "io_error",
+            ToolError::Failed(_) => "failed",
+            ToolError::Panicked { .. } => "panicked",
+            ToolError::Cancelled => "cancelled",
+            ToolError::Denied(_) => "denied",
+            ToolError::AccessDenied { .. } => "access_denied",
+        }
+    }
+
+    /// 由 panic 负载生成错误
+    pub fn from_panic(tool: &str, payload: Box<dyn Any + Send>) -> Self {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "未知错误".to_string());
+        ToolError::Panicked {
+            tool: tool.to_string(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolError::InvalidArguments(message) => write!(f, "参数错误: {}", message),
+            ToolError::Io(error) => write!(f, "IO 错误: {}", error),
+            ToolError::Failed(message) => write!(f, "{}", message),
+            ToolError::Panicked { tool, message } => {
+                write!(f, "工具 {} 执行时发生内部错误: {}", tool, message)
+            }
+            ToolError::Cancelled => write!(f, "调用已取消"),
+            ToolError::Denied(reason) => write!(f, "调用未获批准: {}", reason),
+            ToolError::AccessDenied { path, reason } => {
+                write!(f, "拒绝访问 {}: {}", path, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+impl From<io::Error> for ToolError {
+    fn from(error: io::Error) -> Self {
+        ToolError::Io(error)
+    }
+}
+
+impl From<ToolError> for ToolCallResult {
+    fn from(error: ToolError) -> Self {
+        let message = error.to_string();
+        let mut structured = serde_json::json!({
+            "error": error.code(),
+            "message": message,
+        });
+        match &error {
+            ToolError::Panicked { tool, .. } => structured["tool"] = tool.clone().into(),
+            ToolError::AccessDenied { path, .. } => structured["path"] = path.clone().into(),
+            _ => {}
+        }
+        ToolCallResult {
+            content: vec![ToolContent {
+                content_type: "text".to_string(),
+                text: Some(message),
+                data: None,
+                mime_type: None,
+                annotations: None,
+                resource: None,
+            }],
+            is_error: Some(true),
+            structured_content: Some(structured),
+            meta: None,
+        }
+    }
+}
+
+impl From<Result<ToolCallResult, ToolError>> for ToolCallResult {
+    fn from(result: Result<ToolCallResult, ToolError>) -> Self {
+        result.unwrap_or_else(ToolCallResult::from)
+    }
+}
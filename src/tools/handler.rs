@@ -1,133 +1,73 @@
+use std::{
+    collections::BTreeSet,
+    net::IpAddr,
+    panic::AssertUnwindSafe,
+    time::{Duration, Instant},
+};
+
+use futures::FutureExt;
+use tracing::Instrument;
+
+use crate::config::{Config, config};
+use crate::middleware::auth::AuthIdentity;
+use crate::server::{
+    metrics::{GaugeGuard, metrics, tool_label},
+    shutdown, state,
+};
 use crate::tools::{
-    public::{
-        network::{ping_tool::handle_ping_tool, read_ip_tool::handle_read_ip_tool},
-        system::{
-            file::{
-                list_files_tool::handle_list_files_tool, read_file_tool::handle_read_file_tool,
-            }, random_string_tool::handle_random_string_tool, system_tool::handle_get_system_type, time_tool::handle_get_current_time
-        },
-    },
+    approval, audit, cache, concurrency,
+    context::{self, ToolContext},
+    error::ToolError,
+    rate_limit::{self, RATE_LIMITED_CODE},
+    registry::{apply_defaults, registry},
     tool_dto::*,
 };
 
+/// 当前配置下启用的工具名称，用于判断工具集合是否变化
+pub fn available_tool_names(config: &Config) -> BTreeSet<String> {
+    registry()
+        .list(config)
+        .into_iter()
+        .map(|tool| tool.name)
+        .collect()
+}
+
+/// 工具调用超时的结果
+fn timeout_result(tool: &str, deadline: Duration) -> ToolCallResult {
+    let timeout_ms = deadline.as_millis();
+    ToolCallResult {
+        content: vec![ToolContent {
+            content_type: "text".to_string(),
+            text: Some(format!(
+                "工具 {} 调用超时: 超过 {}ms 未完成",
+                tool, timeout_ms
+            )),
+            data: None,
+            mime_type: None,
+            annotations: None,
+            resource: None,
+        }],
+        is_error: Some(true),
+        meta: None,
+        structured_content: Some(serde_json::json!({
+            "error": "timeout",
+            "tool": tool,
+            "timeout_ms": timeout_ms,
+        })),
+    }
+}
+
 /// 内部工具列表处理函数
 pub async fn handle_tools_list_internal(
     request: JsonRpcRequest<ToolsListParams>,
+    identity: Option<&AuthIdentity>,
 ) -> JsonRpcResponse<ToolsListResult> {
-    let tools = vec![
-        Tool {
-            name: "get_system_type".to_string(),
-            title: Some("系统类型信息".to_string()),
-            description: "获取当前运行系统的类型信息,包括操作系统、架构".to_string(),
-            input_schema: ToolInputSchema {
-                schema_type: "object".to_string(),
-                properties: Some(serde_json::json!({})),
-                required: None,
-            },
-            output_schema: None,
-            annotations: None,
-        },
-        Tool {
-            name: "get_current_time".to_string(),
-            title: Some("当前时间".to_string()),
-            description: "获取当前时间".to_string(),
-            input_schema: ToolInputSchema {
-                schema_type: "object".to_string(),
-                properties: Some(serde_json::json!({})),
-                required: None,
-            },
-            output_schema: None,
-            annotations: None,
-        },
-        Tool {
-            name: "ping".to_string(),
-            title: Some("Ping".to_string()),
-            description: "Ping 工具,测试网络连通性".to_string(),
-            input_schema: ToolInputSchema {
-                schema_type: "object".to_string(),
-                properties: Some(serde_json::json!({
-                    "target": {
-                        "type": "string",
-                        "description": "要ping的地址,支持URL,IP:端口,IP地址,域名"
-                    }
-                })),
-                required: Some(vec!["target".to_string()]),
-            },
-            output_schema: None,
-            annotations: None,
-        },
-        Tool {
-            name: "read ip".to_string(),
-            title: Some("查询IP".to_string()),
-            description: "查询域名解析IP及延迟；不传参数时返回本机公网IP".to_string(),
-            input_schema: ToolInputSchema {
-                schema_type: "object".to_string(),
-                properties: Some(serde_json::json!({
-                    "domain": {"type": "string", "description": "要解析的域名，可选"},
-                    "dns": {"type": "string", "description": "自定义DNS服务器，支持 ip 或 ip:port，可选"},
-                    "port": {"type": "number", "description": "用于测延迟的端口，默认80，可选"}
-                })),
-                required: None,
-            },
-            output_schema: None,
-            annotations: None,
-        },
-        Tool {
-            name: "cat file".to_string(),
-            title: Some("读取文件".to_string()),
-            description: "读取文件内容".to_string(),
-            input_schema: ToolInputSchema {
-                schema_type: "object".to_string(),
-                properties: Some(serde_json::json!({
-                    "file_path": {
-                        "type": "string",
-                        "description": "要读取的文件路径"
-                    }
-                })),
-                required: Some(vec!["file_path".to_string()]),
-            },
-            output_schema: None,
-            annotations: None,
-        },
-        Tool {
-            name: "list files".to_string(),
-            title: Some("列出文件".to_string()),
-            description: "列出文件".to_string(),
-            input_schema: ToolInputSchema {
-                schema_type: "object".to_string(),
-                properties: Some(serde_json::json!({
-                    "dir_path": {
-                        "type": "string",
-                        "description": "要列出的文件夹路径"
-                    }
-                })),
-                required: Some(vec!["dir_path".to_string()]),
-            },
-            output_schema: None,
-            annotations: None,
-        },
-        Tool {
-            name: "random string".to_string(),
-            title: Some("随机字符串".to_string()),
-            description: "生成随机字符串".to_string(),
-            input_schema: ToolInputSchema {
-                schema_type: "object".to_string(),
-                properties: Some(serde_json::json!({
-                    "length": {
-                        "type": "number",
-                        "description": "随机字符串的长度,默认长度为8位的大小写字母+数字"
-                    },
-                    "include_special": {
-                        "type": "boolean",
-                        "description": "是否包含特殊字符,默认不包含"
-                    }
-                })),
-                required: None,
-            },
-            output_schema: None,
-            annotations: None,
-        },
-    ];
+    let mut tools = registry().list(&config::current());
+
+    // 只返回当前调用方有权使用的工具
+    if let Some(identity) = identity {
+        tools.retain(|tool| identity.can_use_tool(&tool.name));
+    }
 
     let result = ToolsListResult {
         tools,
@@ -141,24 +81,169 @@ pub async fn handle_tools_list_internal(
     }
 }
 
-/// 内部工具调用处理函数
+/// 工具调用结果分类：success / error / timeout / cancelled / denied / rejected，审计日志与指标共用
+pub fn call_outcome(
+    result: &Result<JsonRpcResponse<ToolCallResult>, JsonRpcError>,
+) -> &'static str {
+    match result {
+        Ok(response) if response.result.is_error == Some(true) => {
+            let error = response
+                .result
+                .structured_content
+                .as_ref()
+                .and_then(|c| c.get("error"))
+                .and_then(|e| e.as_str());
+            match error {
+                Some("timeout") => "timeout",
+                Some("cancelled") => "cancelled",
+                Some("denied") => "denied",
+                _ => "error",
+            }
+        }
+        Ok(_) => "success",
+        Err(_) => "rejected",
+    }
+}
+
+/// 调用结束时记录日志、指标与审计日志；调用在完成前被丢弃（如 HTTP 客户端断开）时在 Drop 中按 cancelled 记录
+struct CallRecord<'a> {
+    identity: Option<&'a AuthIdentity>,
+    session_id: Option<&'a str>,
+    id: JsonRpcId,
+    tool: String,
+    arguments: Option<serde_json::Value>,
+    span: tracing::Span,
+    started: Instant,
+    finished: bool,
+}
+
+impl CallRecord<'_> {
+    fn finish(&mut self, result: &Result<JsonRpcResponse<ToolCallResult>, JsonRpcError>) {
+        self.finished = true;
+        let elapsed = self.started.elapsed();
+        let outcome = call_outcome(result);
+        self.span.record("outcome", outcome);
+        self.span.in_scope(|| {
+            tracing::info!(
+                duration_ms = elapsed.as_millis() as u64,
+                "tool call finished"
+            )
+        });
+        let label = tool_label(&self.tool);
+        metrics()
+            .tool_call_duration
+            .with_label_values(&[label])
+            .observe(elapsed.as_secs_f64());
+        metrics()
+            .tool_calls
+            .with_label_values(&[label, outcome])
+            .inc();
+        audit::record_tool_call(
+            self.identity,
+            self.session_id,
+            &self.tool,
+            self.arguments.take(),
+            result,
+            elapsed,
+        );
+    }
+}
+
+impl Drop for CallRecord<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.span
+            .in_scope(|| tracing::info!("tool call dropped before completion"));
+        let result = Ok(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: self.id.clone(),
+            result: ToolError::Cancelled.into(),
+        });
+        self.finish(&result);
+    }
+}
+
+/// 内部工具调用处理函数，每次调用（包括被拒绝与中途丢弃的调用）都写入审计日志
 pub async fn handle_tool_call_internal(
     request: JsonRpcRequest<ToolCallParams>,
+    identity: Option<&AuthIdentity>,
+    session_id: Option<&str>,
+    peer: Option<IpAddr>,
+) -> Result<JsonRpcResponse<ToolCallResult>, JsonRpcError> {
+    let (tool, arguments) = match &request.params {
+        Some(params) => (params.name.clone(), params.arguments.clone()),
+        None => (String::new(), None),
+    };
+    let span = tracing::info_span!("tool.call", tool = %tool, outcome = tracing::field::Empty);
+    let mut record = CallRecord {
+        identity,
+        session_id,
+        id: request.id.clone(),
+        tool,
+        arguments,
+        span: span.clone(),
+        started: Instant::now(),
+        finished: false,
+    };
+    let result = {
+        let _in_flight = GaugeGuard::new(
+            metrics()
+                .tool_calls_in_flight
+                .with_label_values(&[tool_label(&record.tool)]),
+        );
+        // 关闭时等待已开始的调用完成
+        shutdown::in_flight()
+            .track_future(call_tool(request, identity, session_id, peer).instrument(span))
+            .await
+    };
+    record.finish(&result);
+    result
+}
+
+async fn call_tool(
+    request: JsonRpcRequest<ToolCallParams>,
+    identity: Option<&AuthIdentity>,
+    session_id: Option<&str>,
+    peer: Option<IpAddr>,
 ) -> Result<JsonRpcResponse<ToolCallResult>, JsonRpcError> {
     let params = request.params.unwrap_or(ToolCallParams {
         name: "".to_string(),
         arguments: None,
+        meta: None,
     });
 
-    let result = match params.name.as_str() {
-        "get_system_type" => handle_get_system_type(params.arguments),
-        "get_current_time" => handle_get_current_time(params.arguments),
-        "ping" => handle_ping_tool(params.arguments),
-        "read ip" => handle_read_ip_tool(params.arguments).await,
-        "cat file" => handle_read_file_tool(params.arguments).await,
-        "list files" => handle_list_files_tool(params.arguments).await,
-        "random string" => handle_random_string_tool(params.arguments),
-        _ => {
+    if let Some(identity) = identity
+        && !identity.can_use_tool(&params.name)
+    {
+        return Err(JsonRpcError {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            error: ErrorDetail {
+                code: -32001,
+                message: format!("Tool not permitted for {}: {}", identity.name, params.name),
+                data: None,
+            },
+        });
+    }
+
+    let config = config::current();
+    let registry = registry();
+    let entry = match registry.get(&params.name) {
+        Some(entry) if config.tool_enabled(&params.name) => entry,
+        Some(_) => {
+            return Err(JsonRpcError {
+                jsonrpc: "2.0".to_string(),
+                id: request.id,
+                error: ErrorDetail {
+                    code: -32602,
+                    message: format!("Tool disabled: {}", params.name),
+                    data: None,
+                },
+            });
+        }
+        None => {
             return Err(JsonRpcError {
                 jsonrpc: "2.0".to_string(),
                 id: request.id,
@@ -171,6 +256,140 @@ pub async fn handle_tool_call_internal(
         }
     };
 
+    if let Some(limit) = config.tool_rate_limit(&params.name) {
+        let client = rate_limit::client_key(identity, session_id, peer);
+        if let Err(retry_after) = rate_limit::check(&client, &params.name, limit) {
+            let retry_after_ms = retry_after.as_millis() + 1;
+            return Err(JsonRpcError {
+                jsonrpc: "2.0".to_string(),
+                id: request.id,
+                error: ErrorDetail {
+                    code: RATE_LIMITED_CODE,
+                    message: format!(
+                        "Rate limit exceeded for {}, retry after {}ms",
+                        params.name, retry_after_ms
+                    ),
+                    data: Some(serde_json::json!({
+                        "tool": params.name,
+                        "retry_after_ms": retry_after_ms,
+                    })),
+                },
+            });
+        }
+    }
+
+    let arguments = match config.tools.get(&params.name) {
+        Some(tool_config) => apply_defaults(params.arguments, &tool_config.defaults),
+        None => params.arguments,
+    };
+
+    let progress_token = params
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.get("progressToken"))
+        .cloned();
+    let ctx = ToolContext::new(session_id, progress_token);
+    let cancellation = ctx.cancellation_token().clone();
+    // 同一会话可以通过 notifications/cancelled 取消进行中的调用
+    let _registration = match (session_id, serde_json::to_value(&request.id)) {
+        (Some(session_id), Ok(id)) => {
+            match context::register_call(session_id, &id, cancellation.clone()) {
+                Some(registration) => Some(registration),
+                None => {
+                    return Err(JsonRpcError {
+                        jsonrpc: "2.0".to_string(),
+                        id: request.id,
+                        error: ErrorDetail {
+                            code: -32600,
+                            message: format!("Request id {} is already in use in this session", id),
+                            data: None,
+                        },
+                    });
+                }
+            }
+        }
+        _ => None,
+    };
+
+    // 需要人工审批的调用在执行前等待操作员决定，等待期间同样可以被取消
+    let approval = tokio::select! {
+        biased;
+        _ = cancellation.cancelled() => Err(ToolError::Cancelled),
+        result = approval::check(entry, arguments.as_ref(), identity, session_id) => result,
+    };
+    if let Err(error) = approval {
+        return Ok(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result: error.into(),
+        });
+    }
+
+    // 只读且结果确定的工具配置了缓存时间后，相同参数的调用直接返回缓存结果
+    let cache_ttl = config
+        .tool_cache_ttl(&params.name)
+        .filter(|_| entry.cacheable());
+    let cache_key = cache_ttl.map(|_| {
+        cache::cache_key(
+            state::current().id,
+            identity,
+            &params.name,
+            arguments.as_ref(),
+        )
+    });
+    if let (Some(ttl), Some(key)) = (cache_ttl, &cache_key)
+        && let Some((mut result, age)) = cache::get(key, ttl)
+    {
+        result.meta = Some(serde_json::json!({
+            "cache": "hit",
+            "cache_age_ms": age.as_millis() as u64,
+        }));
+        return Ok(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: request.id,
+            result,
+        });
+    }
+
+    let deadline = config.tool_timeout(&params.name);
+    let max_concurrency = config
+        .tools
+        .get(&params.name)
+        .and_then(|tool| tool.max_concurrency);
+    let call = async {
+        let _permit = match max_concurrency {
+            Some(max) => Some(concurrency::acquire(&params.name, max).await),
+            None => None,
+        };
+        // 工具内部 panic 时返回错误结果，不影响连接与其他调用
+        match AssertUnwindSafe((entry.handler)(ctx, arguments))
+            .catch_unwind()
+            .await
+        {
+            Ok(result) => result,
+            Err(payload) => {
+                let error = ToolError::from_panic(&params.name, payload);
+                tracing::error!("{}", error);
+                error.into()
+            }
+        }
+    };
+    let mut result = tokio::select! {
+        biased;
+        _ = cancellation.cancelled() => ToolError::Cancelled.into(),
+        result = tokio::time::timeout(deadline, call) => match result {
+            Ok(result) => result,
+            Err(_) => {
+                cancellation.cancel();
+                timeout_result(&params.name, deadline)
+            }
+        },
+    };
+    if let Some(key) = cache_key {
+        cache::put(key, &result);
+        result.meta = Some(serde_json::json!({ "cache": "miss" }));
+    }
+
     Ok(JsonRpcResponse {
         jsonrpc: "2.0".to_string(),
         id: request.id,
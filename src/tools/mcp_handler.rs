@@ -1,29 +1,205 @@
-use axum::body::Bytes;
+use std::{net::IpAddr, time::Duration};
+
+use axum::{
+    Extension,
+    body::Bytes,
+    extract::ConnectInfo,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
 use serde_json;
+use tracing::Instrument;
+
+use crate::{
+    config::config,
+    middleware::auth::AuthIdentity,
+    server::{
+        metrics::{method_label, metrics},
+        peer::PeerAddr,
+    },
+    tools::{
+        rate_limit::{self, RATE_LIMITED_CODE},
+        session::{self, ClientInfo, SESSION_HEADER},
+    },
+};
 
 /// 通用MCP请求处理器，能够处理任何JSON格式
-pub async fn handle_generic_mcp_request(body: Bytes) -> axum::Json<serde_json::Value> {
+#[tracing::instrument(name = "mcp.http", skip_all, fields(session_id))]
+pub async fn handle_generic_mcp_request(
+    identity: Option<Extension<AuthIdentity>>,
+    peer: Option<Extension<ConnectInfo<PeerAddr>>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
     // 尝试解析JSON
     let json_value: serde_json::Value = match serde_json::from_slice(&body) {
         Ok(val) => val,
         Err(_) => {
             // 如果解析失败，返回JSON-RPC错误
-            return axum::Json(serde_json::json!({
+            return axum::Json(parse_error_response()).into_response();
+        }
+    };
+
+    // 初始化时分配会话ID，客户端之后通过 Mcp-Session-Id 请求头携带
+    let identity = identity.as_ref().map(|i| &i.0);
+    let mut session_id = match request_session(&headers, identity) {
+        Ok(session_id) => session_id,
+        Err(error) => return error.into_response(),
+    };
+    let peer = peer.and_then(|Extension(ConnectInfo(peer))| peer.ip());
+    let mut response_headers = HeaderMap::new();
+    if session_id.is_none() && json_value.get("method") == Some(&"initialize".into()) {
+        if let Err(retry_after) = check_new_session(identity, peer) {
+            return session_rate_limited(json_value.get("id"), retry_after);
+        }
+        let new_id = match session::create(identity) {
+            Ok(new_id) => new_id,
+            Err(e) => {
+                tracing::warn!(error = %e, "Rejecting initialize");
+                return sessions_full(json_value.get("id"));
+            }
+        };
+        if let Ok(value) = HeaderValue::from_str(&new_id) {
+            response_headers.insert(SESSION_HEADER, value);
+        }
+        session_id = Some(new_id);
+    }
+    if let Some(session_id) = &session_id {
+        tracing::Span::current().record("session_id", session_id.as_str());
+    }
+
+    // 通知不需要响应，HTTP 下返回空对象
+    let reply = dispatch_mcp_message(json_value, identity, session_id.as_deref(), peer)
+        .await
+        .unwrap_or_else(|| serde_json::json!({}));
+    (response_headers, axum::Json(reply)).into_response()
+}
+
+/// 按当前配置检查新建会话的频率限制，超出时返回建议的等待时间
+pub fn check_new_session(
+    identity: Option<&AuthIdentity>,
+    peer: Option<IpAddr>,
+) -> Result<(), Duration> {
+    match &config::current().session_rate_limit {
+        Some(limit) => rate_limit::check_new_session(identity, peer, limit),
+        None => Ok(()),
+    }
+}
+
+/// 新建会话超出频率限制时的响应：HTTP 429 与 JSON-RPC 错误
+fn session_rate_limited(id: Option<&serde_json::Value>, retry_after: Duration) -> Response {
+    let retry_after_ms = retry_after.as_millis() + 1;
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        axum::Json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": RATE_LIMITED_CODE,
+                "message": format!("Too many new sessions, retry after {}ms", retry_after_ms),
+                "data": {"retry_after_ms": retry_after_ms}
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// 会话数量已达上限时的响应：HTTP 503 与 JSON-RPC 错误
+fn sessions_full(id: Option<&serde_json::Value>) -> Response {
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        axum::Json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": {
+                "code": -32000,
+                "message": "Too many sessions, retry later"
+            }
+        })),
+    )
+        .into_response()
+}
+
+/// 客户端结束会话：DELETE 请求携带 Mcp-Session-Id，之后该会话ID不再有效
+pub async fn handle_delete_session(
+    identity: Option<Extension<AuthIdentity>>,
+    headers: HeaderMap,
+) -> Response {
+    match request_session(&headers, identity.as_ref().map(|i| &i.0)) {
+        Ok(Some(session_id)) => {
+            session::remove_session(&session_id);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Ok(None) => (StatusCode::BAD_REQUEST, "Mcp-Session-Id header required").into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// JSON解析失败时的JSON-RPC错误响应
+pub fn parse_error_response() -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": {
+            "code": -32700,
+            "message": "Parse error"
+        }
+    })
+}
+
+/// 请求携带的会话ID未由 initialize 分配或已过期
+pub struct SessionNotFound;
+
+impl IntoResponse for SessionNotFound {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::NOT_FOUND,
+            axum::Json(serde_json::json!({
                 "jsonrpc": "2.0",
                 "id": null,
                 "error": {
-                    "code": -32700,
-                    "message": "Parse error"
+                    "code": -32001,
+                    "message": "Session not found"
                 }
-            }));
-        }
+            })),
+        )
+            .into_response()
+    }
+}
+
+/// 读取请求携带的会话ID；会话不存在或由其他认证身份创建时返回 404，客户端需重新初始化
+pub fn request_session(
+    headers: &HeaderMap,
+    identity: Option<&AuthIdentity>,
+) -> Result<Option<String>, SessionNotFound> {
+    let Some(session_id) = headers.get(SESSION_HEADER) else {
+        return Ok(None);
     };
+    session_id
+        .to_str()
+        .ok()
+        .filter(|id| session::get_owned(id, identity).is_some())
+        .map(|id| Some(id.to_string()))
+        .ok_or(SessionNotFound)
+}
 
+/// MCP消息分发入口，HTTP 与 WebSocket 传输共用；通知消息返回 None
+pub async fn dispatch_mcp_message(
+    json_value: serde_json::Value,
+    identity: Option<&AuthIdentity>,
+    session_id: Option<&str>,
+    peer: Option<IpAddr>,
+) -> Option<serde_json::Value> {
     let method = json_value
         .get("method")
         .and_then(|m| m.as_str())
         .unwrap_or("unknown");
 
+    metrics()
+        .requests
+        .with_label_values(&[method_label(method)])
+        .inc();
+
     let id = json_value.get("id").cloned();
     let params = json_value.get("params").cloned();
 
@@ -45,31 +221,58 @@ pub async fn handle_generic_mcp_request(body: Bytes) -> axum::Json<serde_json::V
         params,
     };
 
-    handle_mcp_request_internal(request).await
+    let span = tracing::info_span!(
+        "mcp.request",
+        request_id = %json_value.get("id").unwrap_or(&serde_json::Value::Null),
+        method = %request.method,
+    );
+    handle_mcp_request_internal(request, identity, session_id, peer)
+        .instrument(span)
+        .await
 }
 
 /// 内部MCP请求处理逻辑
 async fn handle_mcp_request_internal(
     request: crate::tools::tool_dto::FlexibleJsonRpcRequest<serde_json::Value>,
-) -> axum::Json<serde_json::Value> {
+    identity: Option<&AuthIdentity>,
+    session_id: Option<&str>,
+    peer: Option<IpAddr>,
+) -> Option<serde_json::Value> {
     // 如果没有ID，说明这是一个通知，不需要响应
     let request_id = match &request.id {
         Some(id) => id.clone(),
         None => {
-            // 可以不响应或返回空响应
-            return axum::Json(serde_json::json!({}));
+            // 客户端取消同一会话中进行中的工具调用
+            if request.method == "notifications/cancelled"
+                && let Some(session_id) = session_id
+                && let Some(request_id) = request.params.as_ref().and_then(|p| p.get("requestId"))
+            {
+                crate::tools::context::cancel_call(session_id, request_id);
+            }
+            return None;
         }
     };
 
     match request.method.as_str() {
         "initialize" => {
+            // 记录客户端信息，供该会话的工具调用读取
+            if let Some(session_id) = session_id
+                && let Some(client_info) = request
+                    .params
+                    .as_ref()
+                    .and_then(|p| p.get("clientInfo"))
+                    .and_then(|c| serde_json::from_value::<ClientInfo>(c.clone()).ok())
+                && let Some(session) = session::get(session_id)
+            {
+                session.set_client_info(client_info);
+            }
             let response = crate::tools::tool_dto::JsonRpcResponse {
                 jsonrpc: "2.0".to_string(),
                 id: request_id,
                 result: serde_json::json!({
                     "capabilities": {
                         "tools": {
-                            "listChanged": false
+                            "listChanged": true
                         }
                     },
                     "protocolVersion": "2025-06-18",
@@ -79,7 +282,7 @@ async fn handle_mcp_request_internal(
                     }
                 }),
             };
-            axum::Json(serde_json::to_value(&response).unwrap_or_default())
+            Some(serde_json::to_value(&response).unwrap_or_default())
         }
         "tools/list" => {
             // 使用现有的工具列表处理逻辑
@@ -92,8 +295,9 @@ async fn handle_mcp_request_internal(
                     .as_ref()
                     .and_then(|p| serde_json::from_value(p.clone()).ok()),
             };
-            let response = crate::tools::handler::handle_tools_list_internal(tools_request).await;
-            axum::Json(serde_json::to_value(&response).unwrap_or_default())
+            let response =
+                crate::tools::handler::handle_tools_list_internal(tools_request, identity).await;
+            Some(serde_json::to_value(&response).unwrap_or_default())
         }
         "tools/call" => {
             // 处理工具调用请求
@@ -107,20 +311,17 @@ async fn handle_mcp_request_internal(
                     .and_then(|p| serde_json::from_value(p.clone()).ok()),
             };
 
-            match crate::tools::handler::handle_tool_call_internal(tool_call_request).await {
-                Ok(response) => axum::Json(serde_json::to_value(&response).unwrap_or_default()),
-                Err(error) => {
-                    let error_response = crate::tools::tool_dto::JsonRpcError {
-                        jsonrpc: "2.0".to_string(),
-                        id: request_id,
-                        error: crate::tools::tool_dto::ErrorDetail {
-                            code: -32603,
-                            message: "Internal error during tool call".to_string(),
-                            data: Some(serde_json::to_value(&error).unwrap_or_default()),
-                        },
-                    };
-                    axum::Json(serde_json::to_value(&error_response).unwrap_or_default())
-                }
+            // 工具调用错误（未知工具、无权限、超出频率限制等）原样返回，保留错误码与 data
+            match crate::tools::handler::handle_tool_call_internal(
+                tool_call_request,
+                identity,
+                session_id,
+                peer,
+            )
+            .await
+            {
+                Ok(response) => Some(serde_json::to_value(&response).unwrap_or_default()),
+                Err(error) => Some(serde_json::to_value(&error).unwrap_or_default()),
             }
         }
         _ => {
@@ -133,7 +334,7 @@ async fn handle_mcp_request_internal(
                     data: None,
                 },
             };
-            axum::Json(serde_json::to_value(&error_response).unwrap_or_default())
+            Some(serde_json::to_value(&error_response).unwrap_or_default())
         }
     }
 }
@@ -1,5 +1,18 @@
+pub mod approval;
+pub mod audit;
+pub mod cache;
+pub mod client_request;
+pub mod concurrency;
+pub mod context;
+pub mod error;
 pub mod handler;
 pub mod mcp_handler;
+pub mod notifier;
+pub mod path_policy;
 pub mod public;
+pub mod rate_limit;
+pub mod registry;
+pub mod session;
 pub mod sse_handler;
 pub mod tool_dto;
+pub mod ws_handler;
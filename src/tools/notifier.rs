@@ -0,0 +1,58 @@
+use std::sync::LazyLock;
+
+use tokio::sync::broadcast;
+
+use crate::tools::tool_dto::Notification;
+
+/// 广播中的通知及其接收会话
+#[derive(Debug, Clone)]
+pub struct TargetedNotification {
+    /// 为 None 时发给所有客户端
+    pub session_id: Option<String>,
+    pub notification: Notification,
+}
+
+impl TargetedNotification {
+    /// 该通知是否应转发给指定会话的连接
+    pub fn is_for(&self, session_id: Option<&str>) -> bool {
+        match &self.session_id {
+            None => true,
+            Some(target) => session_id == Some(target.as_str()),
+        }
+    }
+}
+
+/// 服务端主动推送的通知，由 SSE 与 WebSocket 连接订阅后转发给客户端
+static NOTIFICATIONS: LazyLock<broadcast::Sender<TargetedNotification>> =
+    LazyLock::new(|| broadcast::channel(64).0);
+
+/// 订阅服务端通知
+pub fn subscribe() -> broadcast::Receiver<TargetedNotification> {
+    NOTIFICATIONS.subscribe()
+}
+
+fn send(session_id: Option<&str>, method: &str, params: Option<serde_json::Value>) {
+    let _ = NOTIFICATIONS.send(TargetedNotification {
+        session_id: session_id.map(str::to_string),
+        notification: Notification {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        },
+    });
+}
+
+/// 向所有已连接的客户端广播通知，没有订阅者时直接丢弃
+pub fn notify(method: &str, params: Option<serde_json::Value>) {
+    send(None, method, params);
+}
+
+/// 只向指定会话的连接发送通知，如工具调用进度
+pub fn notify_session(session_id: &str, method: &str, params: Option<serde_json::Value>) {
+    send(Some(session_id), method, params);
+}
+
+/// 通知客户端工具列表已变化
+pub fn notify_tools_list_changed() {
+    notify("notifications/tools/list_changed", None);
+}
@@ -0,0 +1,295 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+
+use crate::{config::config::PathPolicyConfig, server::state, tools::error::ToolError};
+
+/// macOS 与 Windows 的默认文件系统不区分大小写，禁止规则也按不区分大小写匹配，
+/// 避免 "~/.SSH/id_rsa" 之类的写法绕过 "**/.ssh/**"
+const CASE_INSENSITIVE_PATHS: bool = cfg!(any(target_os = "macos", windows));
+
+/// 编译路径 glob："*" 不跨越目录分隔符，"**" 匹配任意层目录
+pub fn compile_glob(pattern: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .case_insensitive(CASE_INSENSITIVE_PATHS)
+        .build()
+}
+
+/// 转为绝对路径并按字面消去 "." 与 ".."，不访问文件系统
+fn normalize(path: &Path) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// 文件工具的路径访问策略，所有读取文件或目录的工具都需要先经过 resolve
+pub struct PathPolicy {
+    /// 根目录的字面形式与解析符号链接后的形式（目录不存在时为 None）
+    roots: Vec<(PathBuf, Option<PathBuf>)>,
+    deny_patterns: Vec<String>,
+    deny: GlobSet,
+}
+
+impl PathPolicy {
+    pub fn from_config(config: &PathPolicyConfig) -> Self {
+        let roots = config
+            .allowed_roots
+            .iter()
+            .map(|root| {
+                let root = normalize(Path::new(root));
+                let canonical = std::fs::canonicalize(&root).ok();
+                (root, canonical)
+            })
+            .collect();
+        // 配置加载时已校验过 glob，这里跳过无法编译的规则
+        let mut deny_patterns = Vec::new();
+        let mut builder = GlobSetBuilder::new();
+        for pattern in &config.deny {
+            if let Ok(glob) = compile_glob(pattern) {
+                builder.add(glob);
+                deny_patterns.push(pattern.clone());
+            }
+        }
+        Self {
+            roots,
+            deny_patterns,
+            deny: builder.build().unwrap_or_else(|_| GlobSet::empty()),
+        }
+    }
+
+    /// 当前配置下的路径策略，随配置加载构建
+    pub fn current() -> Arc<Self> {
+        state::current().path_policy()
+    }
+
+    /// 路径匹配的第一条禁止规则
+    pub fn denied_by(&self, path: &Path) -> Option<&str> {
+        // 同时按目录形式（末尾带分隔符）匹配，使 "**/.ssh/**" 也能禁止 .ssh 目录本身
+        let mut as_dir = path.as_os_str().to_owned();
+        as_dir.push(std::path::MAIN_SEPARATOR_STR);
+        [path.as_os_str(), as_dir.as_os_str()]
+            .into_iter()
+            .find_map(|candidate| self.deny.matches(candidate).first().copied())
+            .map(|index| self.deny_patterns[index].as_str())
+    }
+
+    fn within_roots(&self, path: &Path) -> bool {
+        self.roots.is_empty()
+            || self.roots.iter().any(|(root, canonical)| {
+                path.starts_with(root) || canonical.as_ref().is_some_and(|c| path.starts_with(c))
+            })
+    }
+
+    fn check(&self, path: &Path, requested: &str) -> Result<(), ToolError> {
+        if !self.within_roots(path) {
+            return Err(ToolError::AccessDenied {
+                path: requested.to_string(),
+                reason: "不在允许访问的目录内".to_string(),
+            });
+        }
+        if let Some(pattern) = self.denied_by(path) {
+            return Err(ToolError::AccessDenied {
+                path: requested.to_string(),
+                reason: format!("匹配禁止访问规则 {}", pattern),
+            });
+        }
+        Ok(())
+    }
+
+    /// 校验路径并返回解析 ".." 与符号链接后的绝对路径
+    pub async fn resolve(&self, path: &str) -> Result<PathBuf, ToolError> {
+        if path.is_empty() {
+            return Err(ToolError::InvalidArguments("路径不能为空".to_string()));
+        }
+        // 先按字面路径检查，避免通过错误信息探测允许范围之外的文件是否存在
+        let lexical = normalize(Path::new(path));
+        self.check(&lexical, path)?;
+        let canonical = tokio::fs::canonicalize(&lexical)
+            .await
+            .map_err(|e| ToolError::Failed(format!("无法访问 {}: {}", path, e)))?;
+        // 符号链接可能指向允许范围之外或被禁止的文件，按真实路径再检查一次
+        self.check(&canonical, path)?;
+        Ok(canonical)
+    }
+}
+
+/// 配置的根目录都存在且是目录，用于就绪检查
+pub fn check_roots(config: &PathPolicyConfig) -> Result<(), String> {
+    for root in &config.allowed_roots {
+        let metadata = std::fs::metadata(root).map_err(|e| format!("{}: {}", root, e))?;
+        if !metadata.is_dir() {
+            return Err(format!("{}: 不是目录", root));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allowed_roots: &[&Path], deny: &[&str]) -> PathPolicy {
+        PathPolicy::from_config(&PathPolicyConfig {
+            allowed_roots: allowed_roots
+                .iter()
+                .map(|root| root.to_string_lossy().into_owned())
+                .collect(),
+            deny: deny.iter().map(|pattern| pattern.to_string()).collect(),
+        })
+    }
+
+    /// 测试用的临时目录，结束时删除
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "local_mcp_path_policy_{}_{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            // 临时目录本身可能位于符号链接下（如 macOS 的 /tmp），统一用真实路径
+            Self(std::fs::canonicalize(&dir).unwrap())
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn normalize_removes_dot_segments() {
+        assert_eq!(normalize(Path::new("/a/./b/../c")), Path::new("/a/c"));
+        assert_eq!(normalize(Path::new("/a/../../etc")), Path::new("/etc"));
+        assert!(normalize(Path::new("relative/file")).is_absolute());
+    }
+
+    #[test]
+    fn deny_matches_files_and_directories() {
+        let policy = policy(&[], &["**/.ssh/**", "**/*.pem"]);
+        assert_eq!(
+            policy.denied_by(Path::new("/home/me/.ssh/id_rsa")),
+            Some("**/.ssh/**")
+        );
+        assert_eq!(
+            policy.denied_by(Path::new("/home/me/.ssh")),
+            Some("**/.ssh/**")
+        );
+        assert_eq!(
+            policy.denied_by(Path::new("/etc/ssl/server.pem")),
+            Some("**/*.pem")
+        );
+        assert_eq!(policy.denied_by(Path::new("/home/me/.sshrc")), None);
+        assert_eq!(policy.denied_by(Path::new("/home/me/notes.txt")), None);
+    }
+
+    #[test]
+    fn deny_star_does_not_cross_directories() {
+        let policy = policy(&[], &["/data/*.key"]);
+        assert!(policy.denied_by(Path::new("/data/a.key")).is_some());
+        assert!(policy.denied_by(Path::new("/data/sub/a.key")).is_none());
+    }
+
+    #[test]
+    fn deny_case_sensitivity_follows_platform() {
+        let policy = policy(&[], &["**/.ssh/**"]);
+        assert_eq!(
+            policy
+                .denied_by(Path::new("/home/me/.SSH/id_rsa"))
+                .is_some(),
+            CASE_INSENSITIVE_PATHS
+        );
+    }
+
+    #[test]
+    fn within_roots_checks_path_prefix() {
+        let policy_without_roots = policy(&[], &[]);
+        assert!(policy_without_roots.within_roots(Path::new("/anywhere")));
+
+        let policy = policy(&[Path::new("/srv/data/../data")], &[]);
+        assert!(policy.within_roots(Path::new("/srv/data")));
+        assert!(policy.within_roots(Path::new("/srv/data/a/b")));
+        assert!(!policy.within_roots(Path::new("/srv/data2")));
+        assert!(!policy.within_roots(Path::new("/srv")));
+    }
+
+    #[tokio::test]
+    async fn resolve_rejects_parent_segments_leaving_root() {
+        let dir = TempDir::new("parent");
+        let root = dir.0.join("root");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        std::fs::write(dir.0.join("outside.txt"), "outside").unwrap();
+        let policy = policy(&[&root], &[]);
+
+        let inside = root.join("sub/../a.txt");
+        assert_eq!(
+            policy.resolve(&inside.to_string_lossy()).await.unwrap(),
+            root.join("a.txt")
+        );
+        let escape = root.join("../outside.txt");
+        assert!(matches!(
+            policy.resolve(&escape.to_string_lossy()).await,
+            Err(ToolError::AccessDenied { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolve_rejects_missing_paths_outside_root_as_denied() {
+        let dir = TempDir::new("missing");
+        let policy = policy(&[&dir.0], &[]);
+        // 允许范围外的路径不论是否存在都返回同样的错误
+        assert!(matches!(
+            policy.resolve("/nonexistent/local_mcp_file").await,
+            Err(ToolError::AccessDenied { .. })
+        ));
+        assert!(matches!(
+            policy.resolve("").await,
+            Err(ToolError::InvalidArguments(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn resolve_checks_symlink_targets() {
+        let dir = TempDir::new("symlink");
+        let root = dir.0.join("root");
+        let secret = dir.0.join("secret");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::create_dir(&secret).unwrap();
+        std::fs::write(secret.join("key.txt"), "secret").unwrap();
+        std::fs::create_dir(root.join(".ssh")).unwrap();
+        std::fs::write(root.join(".ssh/id_rsa"), "key").unwrap();
+        std::os::unix::fs::symlink(&secret, root.join("escape")).unwrap();
+        std::os::unix::fs::symlink(root.join(".ssh"), root.join("keys")).unwrap();
+        let policy = policy(&[&root], &["**/.ssh/**"]);
+
+        let escape = root.join("escape/key.txt");
+        assert!(matches!(
+            policy.resolve(&escape.to_string_lossy()).await,
+            Err(ToolError::AccessDenied { .. })
+        ));
+        let denied = root.join("keys/id_rsa");
+        match policy.resolve(&denied.to_string_lossy()).await {
+            Err(ToolError::AccessDenied { reason, .. }) => assert!(reason.contains("**/.ssh/**")),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
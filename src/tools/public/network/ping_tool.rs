@@ -1,12 +1,22 @@
+use crate::mcp_tool;
 use crate::tools::tool_dto::{ToolCallResult, ToolContent};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::net::TcpStream;
 use std::time::{Duration, Instant};
 use url::Url;
 
-#[derive(Debug, Deserialize)]
-struct PingArgs {
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct PingArgs {
+    /// 要ping的地址,支持URL,IP:端口,IP地址,域名
     target: String,
+    /// 连接超时毫秒数,默认5000
+    #[serde(default = "default_timeout_ms")]
+    timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    5000
 }
 
 #[derive(Debug, Serialize)]
@@ -25,7 +35,6 @@ enum PingStatus {
     ConnectionFailed,
     Timeout,
     InvalidFormat,
-    MissingArguments,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,15 +52,15 @@ fn parse_target(target: &str) -> Result<String, String> {
     }
 
     // 尝试解析为 URL
-    if let Ok(url) = Url::parse(target) {
-        if let Some(host) = url.host_str() {
-            let port = url.port().unwrap_or_else(|| match url.scheme() {
-                "https" => 443,
-                "http" => 80,
-                _ => 80,
-            });
-            return Ok(format!("{}:{}", host, port));
-        }
+    if let Ok(url) = Url::parse(target)
+        && let Some(host) = url.host_str()
+    {
+        let port = url.port().unwrap_or_else(|| match url.scheme() {
+            "https" => 443,
+            "http" => 80,
+            _ => 80,
+        });
+        return Ok(format!("{}:{}", host, port));
     }
 
     // 检查是否为 IP:端口格式
@@ -78,7 +87,7 @@ fn parse_target(target: &str) -> Result<String, String> {
     Err("无效的目标地址格式".to_string())
 }
 
-fn ping_tool(target: &str) -> ToolCallResult {
+fn ping_tool(target: &str, timeout_ms: u64) -> ToolCallResult {
     // 解析目标地址
     let parsed_target = match parse_target(target) {
         Ok(addr) => addr,
@@ -96,15 +105,17 @@ fn ping_tool(target: &str) -> ToolCallResult {
                     data: None,
                     mime_type: None,
                     annotations: None,
+                    resource: None,
                 }],
                 is_error: Some(true),
+                meta: None,
                 structured_content: Some(serde_json::to_value(error_result).unwrap()),
             };
         }
     };
 
     let start_time = Instant::now();
-    let timeout = Duration::from_secs(5);
+    let timeout = Duration::from_millis(timeout_ms.clamp(100, 60_000));
 
     let connection_result = std::panic::catch_unwind(|| {
         // 这里系统会自动处理域名解析
@@ -145,8 +156,10 @@ fn ping_tool(target: &str) -> ToolCallResult {
                     data: None,
                     mime_type: None,
                     annotations: None,
+                    resource: None,
                 }],
                 is_error: Some(false),
+                meta: None,
                 structured_content: Some(serde_json::to_value(ping_result).unwrap()),
             }
         }
@@ -171,8 +184,10 @@ fn ping_tool(target: &str) -> ToolCallResult {
                     data: None,
                     mime_type: None,
                     annotations: None,
+                    resource: None,
                 }],
                 is_error: Some(true),
+                meta: None,
                 structured_content: Some(serde_json::to_value(ping_result).unwrap()),
             }
         }
@@ -194,8 +209,10 @@ fn ping_tool(target: &str) -> ToolCallResult {
                     data: None,
                     mime_type: None,
                     annotations: None,
+                    resource: None,
                 }],
                 is_error: Some(true),
+                meta: None,
                 structured_content: Some(serde_json::to_value(ping_result).unwrap()),
             }
         }
@@ -204,50 +221,43 @@ fn ping_tool(target: &str) -> ToolCallResult {
 /**
  * 处理 ping 工具调用
  */
-pub fn handle_ping_tool(_args: Option<serde_json::Value>) -> ToolCallResult {
-    let args: PingArgs = match _args {
-        Some(args) => match serde_json::from_value(args) {
-            Ok(parsed_args) => parsed_args,
-            Err(_) => {
-                let error_result = ErrorResult {
-                    error: "参数格式错误，需要包含 target 字段".to_string(),
-                    target: None,
-                    status: PingStatus::InvalidFormat,
-                };
-
-                return ToolCallResult {
-                    content: vec![ToolContent {
-                        content_type: "text".to_string(),
-                        text: Some("参数格式错误，需要包含 target 字段".to_string()),
-                        data: None,
-                        mime_type: None,
-                        annotations: None,
-                    }],
-                    is_error: Some(true),
-                    structured_content: Some(serde_json::to_value(error_result).unwrap()),
-                };
-            }
-        },
-        None => {
+#[mcp_tool(
+    name = "ping",
+    title = "Ping",
+    description = "Ping 工具,测试网络连通性",
+    read_only,
+    open_world,
+    non_deterministic
+)]
+pub async fn handle_ping_tool(args: PingArgs) -> ToolCallResult {
+    // 阻塞式连接放到阻塞线程池执行，避免占用异步运行时的工作线程
+    let target = args.target.clone();
+    // 阻塞线程中沿用当前调用的追踪上下文
+    let span = tracing::info_span!("ping", target = %target, timeout_ms = args.timeout_ms);
+    match tokio::task::spawn_blocking(move || span.in_scope(|| ping_tool(&target, args.timeout_ms)))
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
             let error_result = ErrorResult {
-                error: "缺少参数".to_string(),
-                target: None,
-                status: PingStatus::MissingArguments,
+                error: format!("执行失败: {}", e),
+                target: Some(args.target),
+                status: PingStatus::ConnectionFailed,
             };
 
-            return ToolCallResult {
+            ToolCallResult {
                 content: vec![ToolContent {
                     content_type: "text".to_string(),
-                    text: Some("缺少参数".to_string()),
+                    text: Some(error_result.error.clone()),
                     data: None,
                     mime_type: None,
                     annotations: None,
+                    resource: None,
                 }],
                 is_error: Some(true),
+                meta: None,
                 structured_content: Some(serde_json::to_value(error_result).unwrap()),
-            };
+            }
         }
-    };
-
-    ping_tool(&args.target)
+    }
 }
@@ -1,4 +1,7 @@
+use crate::mcp_tool;
+use crate::server::metrics::metrics;
 use crate::tools::tool_dto::{ToolCallResult, ToolContent};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::net::{IpAddr, SocketAddr};
 use std::time::Instant;
@@ -8,14 +11,17 @@ use trust_dns_resolver::config::{
     NameServerConfig, NameServerConfigGroup, Protocol, ResolverConfig, ResolverOpts,
 };
 
-#[derive(Debug, Deserialize)]
-struct ReadIpArgs {
-    #[serde(skip_serializing_if = "Option::is_none")]
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReadIpArgs {
+    /// 要解析的域名，可选
     domain: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// 自定义DNS服务器，支持 ip 或 ip:port，可选
     dns: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// 用于测延迟的端口，默认80，可选
     port: Option<u16>,
+    /// 未指定 dns 时使用的DNS服务器列表，默认 1.1.1.1 与 8.8.8.8，可选
+    #[serde(default)]
+    dns_servers: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -30,7 +36,6 @@ struct IpLatency {
 #[serde(rename_all = "snake_case")]
 enum ReadIpStatus {
     Success,
-    InvalidArguments,
     ResolveFailed,
     NetworkError,
 }
@@ -58,8 +63,10 @@ fn error_result(message: &str, status: ReadIpStatus) -> ToolCallResult {
             data: None,
             mime_type: None,
             annotations: None,
+            resource: None,
         }],
         is_error: Some(true),
+        meta: None,
         structured_content: Some(serde_json::json!({
             "error": message,
             "status": status,
@@ -67,6 +74,7 @@ fn error_result(message: &str, status: ReadIpStatus) -> ToolCallResult {
     }
 }
 
+#[tracing::instrument]
 async fn fetch_public_ip() -> Option<String> {
     // 尝试多个公共服务，任一成功即可
     let clients = vec![
@@ -75,16 +83,13 @@ async fn fetch_public_ip() -> Option<String> {
     ];
 
     for (url, key) in clients {
-        if let Ok(resp) = timeout(Duration::from_secs(5), reqwest::get(url)).await {
-            if let Ok(resp) = resp {
-                if resp.status().is_success() {
-                    if let Ok(val) = resp.json::<serde_json::Value>().await {
-                        if let Some(ip) = val.get(key).and_then(|v| v.as_str()) {
-                            return Some(ip.to_string());
-                        }
-                    }
-                }
-            }
+        if let Ok(resp) = timeout(Duration::from_secs(5), reqwest::get(url)).await
+            && let Ok(resp) = resp
+            && resp.status().is_success()
+            && let Ok(val) = resp.json::<serde_json::Value>().await
+            && let Some(ip) = val.get(key).and_then(|v| v.as_str())
+        {
+            return Some(ip.to_string());
         }
     }
 
@@ -94,14 +99,12 @@ async fn fetch_public_ip() -> Option<String> {
         reqwest::get("https://ipv4.icanhazip.com"),
     )
     .await
+        && let Ok(resp) = resp
+        && let Ok(text) = resp.text().await
     {
-        if let Ok(resp) = resp {
-            if let Ok(text) = resp.text().await {
-                let t = text.trim();
-                if !t.is_empty() {
-                    return Some(t.to_string());
-                }
-            }
+        let t = text.trim();
+        if !t.is_empty() {
+            return Some(t.to_string());
         }
     }
 
@@ -119,25 +122,31 @@ fn parse_dns(dns: &str) -> Option<SocketAddr> {
     None
 }
 
-async fn build_resolver(dns: Option<&str>) -> Result<(TokioAsyncResolver, Option<String>), String> {
-    if let Some(d) = dns {
-        let sa = parse_dns(d).ok_or_else(|| "无效的DNS地址".to_string())?;
-        let mut group = NameServerConfigGroup::new();
+/// 未指定DNS服务器时使用的公共DNS
+const DEFAULT_DNS_SERVERS: [&str; 2] = ["1.1.1.1", "8.8.8.8"];
+
+pub async fn build_resolver(
+    dns: Option<&str>,
+    dns_servers: &[String],
+) -> Result<(TokioAsyncResolver, Option<String>), String> {
+    // 单个 dns 参数优先，其次是 dns_servers，最后使用通用公共DNS
+    // （避免因系统配置API差异导致编译失败）
+    let servers: Vec<&str> = match dns {
+        Some(d) => vec![d],
+        None if !dns_servers.is_empty() => dns_servers.iter().map(String::as_str).collect(),
+        None => DEFAULT_DNS_SERVERS.to_vec(),
+    };
+
+    let mut group = NameServerConfigGroup::new();
+    let mut used = Vec::new();
+    for server in servers {
+        let sa = parse_dns(server).ok_or_else(|| format!("无效的DNS地址: {}", server))?;
         group.push(NameServerConfig::new(sa, Protocol::Udp));
-        let cfg = ResolverConfig::from_parts(None, vec![], group);
-        let resolver = TokioAsyncResolver::tokio(cfg, ResolverOpts::default());
-        Ok((resolver, Some(sa.to_string())))
-    } else {
-        // 使用通用公共DNS作为默认（避免因系统配置API差异导致编译失败）
-        let mut group = NameServerConfigGroup::new();
-        let cf = SocketAddr::new("1.1.1.1".parse().unwrap(), 53);
-        let gg = SocketAddr::new("8.8.8.8".parse().unwrap(), 53);
-        group.push(NameServerConfig::new(cf, Protocol::Udp));
-        group.push(NameServerConfig::new(gg, Protocol::Udp));
-        let cfg = ResolverConfig::from_parts(None, vec![], group);
-        let resolver = TokioAsyncResolver::tokio(cfg, ResolverOpts::default());
-        Ok((resolver, Some("1.1.1.1,8.8.8.8".to_string())))
+        used.push(sa.to_string());
     }
+    let cfg = ResolverConfig::from_parts(None, vec![], group);
+    let resolver = TokioAsyncResolver::tokio(cfg, ResolverOpts::default());
+    Ok((resolver, Some(used.join(","))))
 }
 
 async fn measure_latency(ip: IpAddr, port: u16) -> IpLatency {
@@ -168,12 +177,14 @@ async fn measure_latency(ip: IpAddr, port: u16) -> IpLatency {
     }
 }
 
+#[tracing::instrument(skip(dns_servers))]
 async fn resolve_and_rank(
     domain: &str,
     dns: Option<&str>,
+    dns_servers: &[String],
     port: u16,
 ) -> Result<(Vec<IpLatency>, Option<String>), String> {
-    let (resolver, dns_used) = build_resolver(dns).await?;
+    let (resolver, dns_used) = build_resolver(dns, dns_servers).await?;
     let lookup = resolver
         .lookup_ip(domain)
         .await
@@ -202,21 +213,15 @@ use futures::StreamExt;
 /**
  * 处理 read ip 工具调用
  */
-pub async fn handle_read_ip_tool(args: Option<serde_json::Value>) -> ToolCallResult {
-    let parsed: ReadIpArgs = match args
-        .map(|v| serde_json::from_value::<ReadIpArgs>(v))
-        .transpose()
-    {
-        Ok(v) => v.unwrap_or(ReadIpArgs {
-            domain: None,
-            dns: None,
-            port: None,
-        }),
-        Err(_) => {
-            return error_result("参数格式错误", ReadIpStatus::InvalidArguments);
-        }
-    };
-
+#[mcp_tool(
+    name = "read ip",
+    title = "查询IP",
+    description = "查询域名解析IP及延迟；不传参数时返回本机公网IP",
+    read_only,
+    open_world,
+    non_deterministic
+)]
+pub async fn handle_read_ip_tool(parsed: ReadIpArgs) -> ToolCallResult {
     if parsed.domain.is_none() {
         // 查询公网IP
         match fetch_public_ip().await {
@@ -237,8 +242,10 @@ pub async fn handle_read_ip_tool(args: Option<serde_json::Value>) -> ToolCallRes
                         data: None,
                         mime_type: None,
                         annotations: None,
+                        resource: None,
                     }],
                     is_error: Some(false),
+                    meta: None,
                     structured_content: Some(serde_json::to_value(res).unwrap()),
                 };
             }
@@ -251,15 +258,17 @@ pub async fn handle_read_ip_tool(args: Option<serde_json::Value>) -> ToolCallRes
     // 域名查询路径
     let domain = parsed.domain.unwrap();
     let port = parsed.port.unwrap_or(80);
-    match resolve_and_rank(&domain, parsed.dns.as_deref(), port).await {
+    let timer = metrics().resolve_duration.start_timer();
+    let ranked = resolve_and_rank(&domain, parsed.dns.as_deref(), &parsed.dns_servers, port).await;
+    timer.observe_duration();
+    match ranked {
         Ok((records, dns_used)) => {
             let mut reachable: Vec<&IpLatency> = records
                 .iter()
                 .filter(|r| r.reachable && r.latency_ms.is_some())
                 .collect();
             reachable.sort_by_key(|r| r.latency_ms.unwrap());
-            let top_ips: Vec<IpLatency> =
-                reachable.into_iter().take(3).map(|r| r.clone()).collect();
+            let top_ips: Vec<IpLatency> = reachable.into_iter().take(3).cloned().collect();
 
             let res = ReadIpResult {
                 domain: Some(domain.clone()),
@@ -288,8 +297,10 @@ pub async fn handle_read_ip_tool(args: Option<serde_json::Value>) -> ToolCallRes
                     data: None,
                     mime_type: None,
                     annotations: None,
+                    resource: None,
                 }],
                 is_error: Some(false),
+                meta: None,
                 structured_content: Some(serde_json::to_value(res).unwrap()),
             }
         }
@@ -1,11 +1,18 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 use tokio::fs;
 
-use crate::tools::tool_dto::{ToolCallResult, ToolContent};
+use crate::mcp_tool;
+use crate::tools::{
+    error::ToolError,
+    path_policy::PathPolicy,
+    tool_dto::{ToolCallResult, ToolContent},
+};
 
-#[derive(Serialize, Deserialize)]
-struct ListFiles {
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ListFiles {
+    /// 要列出的文件夹路径
     dir_path: String,
 }
 
@@ -17,13 +24,20 @@ struct FileInfo {
     is_dir: bool,
 }
 
-async fn list_files_tool(dir_path: String) -> Vec<FileInfo> {
-    let mut files = fs::read_dir(dir_path).await.unwrap();
+async fn list_files_tool(dir_path: String) -> Result<Vec<FileInfo>, ToolError> {
+    let policy = PathPolicy::current();
+    let resolved = policy.resolve(&dir_path).await?;
+    let mut files = fs::read_dir(&resolved)
+        .await
+        .map_err(|e| ToolError::Failed(format!("读取目录 {} 失败: {}", dir_path, e)))?;
     let mut file_infos = Vec::new();
 
-    while let Some(entry) = files.next_entry().await.unwrap() {
-        let path = entry.path();
-        let metadata = entry.metadata().await.unwrap();
+    while let Some(entry) = files.next_entry().await? {
+        // 匹配禁止访问规则的条目不出现在列表中
+        if policy.denied_by(&entry.path()).is_some() {
+            continue;
+        }
+        let metadata = entry.metadata().await?;
 
         let modified = metadata
             .modified()
@@ -33,7 +47,7 @@ async fn list_files_tool(dir_path: String) -> Vec<FileInfo> {
             .as_secs();
 
         let file_info = FileInfo {
-            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            name: entry.file_name().to_string_lossy().to_string(),
             size: metadata.len(),
             modified: format!("{}", modified),
             is_dir: metadata.is_dir(),
@@ -42,45 +56,34 @@ async fn list_files_tool(dir_path: String) -> Vec<FileInfo> {
         file_infos.push(file_info);
     }
 
-    file_infos
+    Ok(file_infos)
 }
 
-pub async fn handle_list_files_tool(args_json: Option<serde_json::Value>) -> ToolCallResult {
-    let args = serde_json::from_value::<ListFiles>(match args_json {
-        Some(args) => args,
-        None => {
-            return ToolCallResult {
-                content: vec![ToolContent {
-                    content_type: "text".to_string(),
-                    text: Some(String::from("参数传递错误")),
-                    data: None,
-                    mime_type: None,
-                    annotations: None,
-                }],
-                is_error: Some(true),
-                structured_content: Some(
-                    serde_json::to_value(String::from("参数传递错误")).unwrap(),
-                ),
-            };
-        }
-    })
-    .unwrap();
-
-    let files = list_files_tool(args.dir_path).await;
+#[mcp_tool(
+    name = "list files",
+    title = "列出文件",
+    description = "列出文件",
+    read_only,
+    path_argument = "dir_path"
+)]
+pub async fn handle_list_files_tool(args: ListFiles) -> Result<ToolCallResult, ToolError> {
+    let files = list_files_tool(args.dir_path).await?;
 
     let result_object = serde_json::json!({
         "files": files
     });
 
-    return ToolCallResult {
+    Ok(ToolCallResult {
         content: vec![ToolContent {
             content_type: "text".to_string(),
-            text: Some(serde_json::to_string(&files).unwrap()),
+            text: Some(serde_json::to_string(&files).unwrap_or_default()),
             data: None,
             mime_type: None,
             annotations: None,
+            resource: None,
         }],
         is_error: Some(false),
+        meta: None,
         structured_content: Some(result_object),
-    };
+    })
 }
@@ -1,90 +1,957 @@
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, SeekFrom},
+    path::Path,
+};
 use tokio::{
     fs::File,
-    io::{AsyncReadExt, BufReader},
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, BufReader},
+};
+
+use crate::mcp_tool;
+use crate::tools::{
+    error::ToolError,
+    path_policy::PathPolicy,
+    tool_dto::{EmbeddedResource, ToolCallResult, ToolContent},
 };
 
-use crate::tools::tool_dto::{ToolCallResult, ToolContent};
+/// 默认返回的最大字节数
+const DEFAULT_MAX_BYTES: u64 = 64 * 1024;
+/// max_bytes 允许的最大值
+const MAX_BYTES_LIMIT: u64 = 1024 * 1024;
+/// head/tail 模式默认读取的行数
+const DEFAULT_EDGE_LINES: u64 = 10;
+const CHUNK_SIZE: usize = 64 * 1024;
+/// 用于判断文件类型的文件头长度
+const SNIFF_BYTES: u64 = 8192;
+/// 以图片内容返回的最大文件大小，调用方指定的 max_bytes 更小时以 max_bytes 为准
+const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+/// 二进制文件默认的十六进制预览字节数
+const HEX_PREVIEW_BYTES: u64 = 512;
+/// 十六进制预览的最大字节数，每字节约占 4 个字符
+const MAX_HEX_BYTES: u64 = 16 * 1024;
+
+/// 读取方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadMode {
+    #[default]
+    Lines,
+    Bytes,
+    Head,
+    Tail,
+}
 
-#[derive(Serialize, Deserialize)]
-struct ReadFile {
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ReadFile {
+    /// 要读取的文件路径
     file_path: String,
-    contents: Option<String>,
+    /// 读取方式：lines 从 offset 行起读取 limit 行（默认）；bytes 从 offset 字节起读取 limit 字节；
+    /// head / tail 读取开头 / 末尾 limit 行（默认 10 行）
+    #[serde(default)]
+    mode: ReadMode,
+    /// 起始位置：lines 模式为跳过的行数，bytes 模式为字节偏移，默认 0；分页时使用上次结果中的 next_offset
+    offset: Option<u64>,
+    /// 最多读取的行数（lines / head / tail）或字节数（bytes），lines 与 bytes 模式默认读到文件末尾
+    limit: Option<u64>,
+    /// 返回内容的字节上限，默认 65536，最大 1048576；超出时截断并在结果中标注。
+    /// 图片默认最多 5 MiB，指定 max_bytes 时不超过 max_bytes
+    max_bytes: Option<u64>,
+    /// 是否统计文件总行数，需要读完整个文件，默认 false；不统计时只有读到文件末尾才返回 total_lines
+    #[serde(default)]
+    count_lines: bool,
+}
+
+/// 读取到的内容窗口
+struct Window {
+    data: Vec<u8>,
+    /// 起始行号（lines 模式）或字节偏移（bytes 模式）；tail 模式未统计总行数时为 None
+    offset: Option<u64>,
+    lines_returned: Option<u64>,
+    /// 文件总行数，只在统计过或读到文件末尾时已知
+    total_lines: Option<u64>,
+    /// 内容因 max_bytes 被截断
+    truncated: bool,
+    /// 截断后只剩一行的一部分
+    partial_line: bool,
+    /// 继续读取时使用的 offset，已读到末尾时为 None
+    next_offset: Option<u64>,
+}
+
+/// 截断处落在多字节字符中间时去掉不完整的字符
+fn trim_partial_char(data: &mut Vec<u8>) {
+    if let Err(e) = std::str::from_utf8(data)
+        && e.error_len().is_none()
+    {
+        data.truncate(e.valid_up_to());
+    }
+}
+
+/// 截断处落在多字节字符中间时去掉开头不完整的字符
+fn trim_leading_partial_char(data: &mut Vec<u8>) {
+    let continuation = data
+        .iter()
+        .take(3)
+        .take_while(|byte| **byte & 0b1100_0000 == 0b1000_0000)
+        .count();
+    data.drain(..continuation);
+}
+
+/// 行数，最后一行没有换行符时同样计入
+fn line_count(data: &[u8]) -> u64 {
+    data.iter().filter(|byte| **byte == b'\n').count() as u64
+        + u64::from(data.last().is_some_and(|byte| *byte != b'\n'))
+}
+
+/// 逐块扫描文件，收集 [start, end) 行的内容，收集量不超过 max_bytes；
+/// 不统计总行数时读完窗口或内容被截断后即停止，不再读取文件的其余部分
+async fn scan_lines(
+    file: &mut File,
+    start: u64,
+    end: Option<u64>,
+    max_bytes: usize,
+    count_lines: bool,
+) -> io::Result<Window> {
+    file.seek(SeekFrom::Start(0)).await?;
+    let mut reader = BufReader::with_capacity(CHUNK_SIZE, file);
+    let mut line = 0u64;
+    let mut data = Vec::new();
+    let mut truncated = false;
+    let mut last_byte = None;
+    let mut stopped_early = false;
+    'scan: loop {
+        let chunk = reader.fill_buf().await?;
+        if chunk.is_empty() {
+            break;
+        }
+        for &byte in chunk {
+            let past_end = end.is_some_and(|end| line >= end);
+            if !count_lines && (past_end || truncated) {
+                stopped_early = true;
+                break 'scan;
+            }
+            if line >= start && !past_end {
+                if data.len() < max_bytes {
+                    data.push(byte);
+                } else {
+                    truncated = true;
+                }
+            }
+            if byte == b'\n' {
+                line += 1;
+            }
+        }
+        last_byte = chunk.last().copied();
+        let len = chunk.len();
+        reader.consume(len);
+    }
+    // 最后一行没有换行符时同样计入总行数
+    let total_lines =
+        (!stopped_early).then(|| line + u64::from(last_byte.is_some_and(|byte| byte != b'\n')));
+
+    let mut partial_line = false;
+    if truncated {
+        // 截断时只保留完整的行；单行就超过上限时保留该行的开头
+        match data.iter().rposition(|byte| *byte == b'\n') {
+            Some(end) => data.truncate(end + 1),
+            None => {
+                trim_partial_char(&mut data);
+                partial_line = true;
+            }
+        }
+    }
+    let lines_returned = line_count(&data);
+    let offset = total_lines.map_or(start, |total| start.min(total));
+    let next_line = offset + lines_returned;
+    Ok(Window {
+        data,
+        offset: Some(offset),
+        lines_returned: Some(lines_returned),
+        total_lines,
+        truncated,
+        partial_line,
+        // 提前停止说明窗口之后还有内容
+        next_offset: total_lines
+            .is_none_or(|total| next_line < total)
+            .then_some(next_line),
+    })
+}
+
+/// 从文件末尾向前逐块读取最后 lines 行，读取量不超过 max_bytes；截断时保留靠近末尾的完整行
+async fn read_tail(
+    file: &mut File,
+    lines: u64,
+    max_bytes: usize,
+    count_lines: bool,
+) -> io::Result<Window> {
+    let size = file.metadata().await?.len();
+    let mut pos = size;
+    // 从 pos 到文件末尾的内容，以及其中窗口开始的位置
+    let mut data = Vec::new();
+    let mut window_start = None;
+    let mut newlines = 0u64;
+    while pos > 0 && window_start.is_none() && data.len() <= max_bytes {
+        let len = pos.min(CHUNK_SIZE as u64) as usize;
+        pos -= len as u64;
+        file.seek(SeekFrom::Start(pos)).await?;
+        let mut chunk = vec![0; len];
+        file.read_exact(&mut chunk).await?;
+        for index in (0..len).rev() {
+            // 文件末尾的换行符属于最后一行，不作为行的分隔
+            if chunk[index] != b'\n' || pos + index as u64 == size - 1 {
+                continue;
+            }
+            newlines += 1;
+            if newlines == lines {
+                window_start = Some(index + 1);
+                break;
+            }
+        }
+        chunk.extend_from_slice(&data);
+        data = chunk;
+    }
+    // 已读到文件开头时可以直接得到总行数
+    let total_lines = if pos == 0 {
+        Some(line_count(&data))
+    } else if count_lines {
+        scan_lines(file, u64::MAX, None, 0, true).await?.total_lines
+    } else {
+        None
+    };
+    let mut data = data.split_off(window_start.unwrap_or(0));
+
+    let truncated = data.len() > max_bytes;
+    let mut partial_line = false;
+    if truncated {
+        data.drain(..data.len() - max_bytes);
+        // 只保留完整的行；最后一行就超过上限时保留该行的末尾
+        match data.iter().position(|byte| *byte == b'\n') {
+            Some(index) if index + 1 < data.len() => {
+                data.drain(..=index);
+            }
+            _ => {
+                trim_leading_partial_char(&mut data);
+                partial_line = true;
+            }
+        }
+    }
+    let lines_returned = line_count(&data);
+    Ok(Window {
+        offset: total_lines.map(|total| total - lines_returned),
+        lines_returned: Some(lines_returned),
+        total_lines,
+        truncated,
+        partial_line,
+        next_offset: None,
+        data,
+    })
+}
+
+/// 从 offset 字节起读取最多 limit 字节，读取量不超过 max_bytes；
+/// 文本文件去掉两端不完整的多字节字符，offset 与 next_offset 随之调整，分段读取时字符不会被拆开
+async fn read_bytes(
+    file: &mut File,
+    offset: u64,
+    limit: Option<u64>,
+    max_bytes: u64,
+    text: bool,
+) -> io::Result<Window> {
+    let size = file.metadata().await?.len();
+    let start = offset.min(size);
+    file.seek(SeekFrom::Start(start)).await?;
+    let wanted = limit.unwrap_or(u64::MAX).min(size - start);
+    let mut data = Vec::new();
+    file.take(wanted.min(max_bytes))
+        .read_to_end(&mut data)
+        .await?;
+    let mut start = start;
+    if text {
+        // 窗口小于一个字符时保留原始字节，保证分段读取总能前进
+        let mut trimmed = data.clone();
+        trim_leading_partial_char(&mut trimmed);
+        let skipped = data.len() - trimmed.len();
+        trim_partial_char(&mut trimmed);
+        if !trimmed.is_empty() {
+            start += skipped as u64;
+            data = trimmed;
+        }
+    }
+    let next = start + data.len() as u64;
+    Ok(Window {
+        offset: Some(start),
+        lines_returned: None,
+        total_lines: None,
+        truncated: wanted > max_bytes,
+        partial_line: false,
+        next_offset: (next < size).then_some(next),
+        data,
+    })
+}
+
+/// 文件类型
+struct FileKind {
+    mime_type: String,
+    binary: bool,
+    /// 文本文件不是合法 UTF-8 时按 Latin-1 解码
+    latin1: bool,
+}
+
+/// 根据文件头的 magic bytes 与扩展名判断 MIME 类型；文件头含 NUL 或 magic bytes 表明是非文本格式时视为二进制，
+/// 其余按文本处理，不是合法 UTF-8 的文本（如 Latin-1）逐字节解码
+async fn sniff(path: &Path, file: &mut File) -> io::Result<FileKind> {
+    let mut head = Vec::new();
+    file.seek(SeekFrom::Start(0)).await?;
+    (&mut *file)
+        .take(SNIFF_BYTES)
+        .read_to_end(&mut head)
+        .await?;
+    let magic = infer::get(&head);
+    let binary = head.contains(&0)
+        || magic.is_some_and(|kind| kind.matcher_type() != infer::MatcherType::Text);
+    // 文件头末尾可能截在多字节字符中间，只有中间出现非法序列时才不是 UTF-8
+    let latin1 = !binary && matches!(std::str::from_utf8(&head), Err(e) if e.error_len().is_some());
+    let mime_type = magic
+        .map(|kind| kind.mime_type().to_string())
+        .or_else(|| {
+            mime_guess::from_path(path)
+                .first()
+                .map(|mime| mime.essence_str().to_string())
+                // 扩展名可能与内容不符（如 TypeScript 的 .ts 被识别为视频），文本文件不采用媒体类型
+                .filter(|mime| {
+                    binary
+                        || !["audio/", "video/", "font/"]
+                            .iter()
+                            .any(|prefix| mime.starts_with(prefix))
+                })
+        })
+        .unwrap_or_else(|| {
+            if binary {
+                "application/octet-stream"
+            } else {
+                "text/plain"
+            }
+            .to_string()
+        });
+    Ok(FileKind {
+        mime_type,
+        binary,
+        latin1,
+    })
 }
 
-async fn read_file_tool(file_path: String) -> Result<String, std::io::Error> {
-    let file = File::open(&file_path).await?;
-    let mut reader = BufReader::new(file);
-    let mut contents = String::new();
-    reader.read_to_string(&mut contents).await?;
-    Ok(contents)
+/// 两次获取的元数据是否属于同一个文件
+#[cfg(unix)]
+fn same_file(a: &std::fs::Metadata, b: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
 }
 
-pub async fn handle_read_file_tool(args_json: Option<serde_json::Value>) -> ToolCallResult {
-    let args = match serde_json::from_value::<ReadFile>(match args_json {
-        Some(args) => args,
-        None => {
-            return ToolCallResult {
-                content: vec![ToolContent {
+#[cfg(not(unix))]
+fn same_file(_: &std::fs::Metadata, _: &std::fs::Metadata) -> bool {
+    true
+}
+
+/// 十六进制预览，每行 16 字节，格式同 hexdump -C
+fn hex_dump(data: &[u8], base_offset: u64) -> String {
+    let mut dump = String::new();
+    for (index, line) in data.chunks(16).enumerate() {
+        let hex: Vec<String> = line.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let (left, right) = hex.split_at(hex.len().min(8));
+        let ascii: String = line
+            .iter()
+            .map(|byte| {
+                if byte.is_ascii_graphic() || *byte == b' ' {
+                    *byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        dump.push_str(&format!(
+            "{:08x}  {:<23}  {:<23}  |{}|\n",
+            base_offset + (index * 16) as u64,
+            left.join(" "),
+            right.join(" "),
+            ascii
+        ));
+    }
+    dump
+}
+
+/// 读取整个文件，最多读取 limit 字节；文件在检查大小之后变得超过 limit 时返回 None
+async fn read_whole(file: &mut File, limit: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut data = Vec::new();
+    file.seek(SeekFrom::Start(0)).await?;
+    file.take(limit.saturating_add(1))
+        .read_to_end(&mut data)
+        .await?;
+    Ok((data.len() as u64 <= limit).then_some(data))
+}
+
+/// 二进制文件：图片返回 image 内容，不超过 max_bytes 的文件作为资源 blob 返回，其余返回十六进制预览
+async fn read_binary(
+    args: &ReadFile,
+    path: &Path,
+    file: &mut File,
+    kind: &FileKind,
+    file_size: u64,
+    max_bytes: u64,
+) -> io::Result<ToolCallResult> {
+    // bytes 模式总是按 offset/limit 返回十六进制内容，便于分段查看
+    let whole_file = args.mode != ReadMode::Bytes;
+    let structured = |encoding: &str| {
+        serde_json::json!({
+            "file_path": args.file_path,
+            "mime_type": kind.mime_type,
+            "binary": true,
+            "file_size": file_size,
+            "encoding": encoding,
+        })
+    };
+
+    let image_limit = args
+        .max_bytes
+        .map_or(MAX_IMAGE_BYTES, |max_bytes| max_bytes.min(MAX_IMAGE_BYTES));
+    if whole_file
+        && kind.mime_type.starts_with("image/")
+        && file_size <= image_limit
+        && let Some(data) = read_whole(file, image_limit).await?
+    {
+        return Ok(ToolCallResult {
+            content: vec![ToolContent {
+                content_type: "image".to_string(),
+                text: None,
+                data: Some(BASE64.encode(&data)),
+                mime_type: Some(kind.mime_type.clone()),
+                annotations: None,
+                resource: None,
+            }],
+            is_error: Some(false),
+            meta: None,
+            structured_content: Some(structured("base64")),
+        });
+    }
+
+    if whole_file
+        && file_size <= max_bytes
+        && let Some(data) = read_whole(file, max_bytes).await?
+    {
+        let uri = url::Url::from_file_path(path)
+            .map(String::from)
+            .unwrap_or_else(|_| format!("file://{}", path.display()));
+        return Ok(ToolCallResult {
+            content: vec![
+                ToolContent {
                     content_type: "text".to_string(),
-                    text: Some("缺少参数".to_string()),
+                    text: Some(format!(
+                        "二进制文件 {}（{}，{} 字节）",
+                        args.file_path, kind.mime_type, file_size
+                    )),
                     data: None,
                     mime_type: None,
                     annotations: None,
-                }],
-                is_error: Some(true),
-                structured_content: Some(serde_json::to_value(String::from("缺少参数")).unwrap()),
-            };
-        }
-    }) {
-        Ok(args) => args,
-        Err(e) => {
-            return ToolCallResult {
-                content: vec![ToolContent {
-                    content_type: "text".to_string(),
-                    text: Some(e.to_string()),
+                    resource: None,
+                },
+                ToolContent {
+                    content_type: "resource".to_string(),
+                    text: None,
                     data: None,
                     mime_type: None,
                     annotations: None,
-                }],
-                is_error: Some(true),
-                structured_content: Some(
-                    serde_json::to_value(String::from("查询路径有误")).unwrap(),
-                ),
-            };
+                    resource: Some(EmbeddedResource {
+                        uri,
+                        mime_type: Some(kind.mime_type.clone()),
+                        text: None,
+                        blob: Some(BASE64.encode(&data)),
+                    }),
+                },
+            ],
+            is_error: Some(false),
+            meta: None,
+            structured_content: Some(structured("base64")),
+        });
+    }
+
+    let (offset, limit) = if whole_file {
+        (0, Some(HEX_PREVIEW_BYTES))
+    } else {
+        (args.offset.unwrap_or(0), args.limit)
+    };
+    let window = read_bytes(file, offset, limit, max_bytes.min(MAX_HEX_BYTES), false).await?;
+    let mut text = format!(
+        "二进制文件 {}（{}，{} 字节）\n{}",
+        args.file_path,
+        kind.mime_type,
+        file_size,
+        hex_dump(&window.data, window.offset.unwrap_or(0))
+    );
+    if let Some(note) = paging_note(ReadMode::Bytes, &window, file_size) {
+        text.push_str(&note);
+    }
+    let mut structured = structured("hex");
+    structured["offset"] = window.offset.into();
+    structured["bytes_returned"] = window.data.len().into();
+    structured["truncated"] = window.truncated.into();
+    structured["next_offset"] = window.next_offset.into();
+    Ok(ToolCallResult {
+        content: vec![ToolContent {
+            content_type: "text".to_string(),
+            text: Some(text),
+            data: None,
+            mime_type: None,
+            annotations: None,
+            resource: None,
+        }],
+        is_error: Some(false),
+        meta: None,
+        structured_content: Some(structured),
+    })
+}
+
+/// 内容被截断或还有剩余内容时附在文本末尾的提示
+fn paging_note(mode: ReadMode, window: &Window, file_size: u64) -> Option<String> {
+    if !window.truncated && window.next_offset.is_none() {
+        return None;
+    }
+    let mut note = match mode {
+        ReadMode::Bytes => format!(
+            "[已返回 {} 字节（从第 {} 字节起），文件共 {} 字节",
+            window.data.len(),
+            window.offset.unwrap_or(0),
+            file_size
+        ),
+        _ => {
+            let mut note = format!("[已返回 {} 行", window.lines_returned.unwrap_or(0));
+            if let Some(offset) = window.offset {
+                note.push_str(&format!("（跳过前 {} 行）", offset));
+            }
+            if let Some(total) = window.total_lines {
+                note.push_str(&format!("，文件共 {} 行", total));
+            }
+            if window.partial_line {
+                note.push_str("；该行超过 max_bytes，可使用 mode=bytes 分段读取");
+            } else if window.truncated {
+                note.push_str("；内容超过 max_bytes 已截断");
+            }
+            note
         }
     };
+    if let Some(next) = window.next_offset {
+        let mode = if mode == ReadMode::Bytes {
+            "bytes"
+        } else {
+            "lines"
+        };
+        note.push_str(&format!("；使用 mode={}, offset={} 继续读取", mode, next));
+    }
+    note.push(']');
+    Some(note)
+}
+
+#[mcp_tool(
+    name = "cat file",
+    title = "读取文件",
+    description = "读取文件内容，支持按行或按字节分段读取大文件；图片以图片内容返回，其他二进制文件返回资源或十六进制预览",
+    read_only,
+    path_argument = "file_path"
+)]
+pub async fn handle_read_file_tool(args: ReadFile) -> Result<ToolCallResult, ToolError> {
+    if args.limit == Some(0) {
+        return Err(ToolError::InvalidArguments("limit 必须大于 0".to_string()));
+    }
+    if args.max_bytes == Some(0) {
+        return Err(ToolError::InvalidArguments(
+            "max_bytes 必须大于 0".to_string(),
+        ));
+    }
+    if matches!(args.mode, ReadMode::Head | ReadMode::Tail) && args.offset.is_some() {
+        return Err(ToolError::InvalidArguments(
+            "head / tail 模式不能指定 offset".to_string(),
+        ));
+    }
 
-    let file_path = args.file_path.clone();
+    let resolved = PathPolicy::current().resolve(&args.file_path).await?;
+    let max_bytes = args
+        .max_bytes
+        .unwrap_or(DEFAULT_MAX_BYTES)
+        .min(MAX_BYTES_LIMIT);
+    let offset = args.offset.unwrap_or(0);
+    let read_error = |e: io::Error| ToolError::Failed(format!("读取文件失败: {}", e));
 
-    match read_file_tool(file_path.clone()).await {
-        Ok(contents) => ToolCallResult {
-            content: vec![ToolContent {
-                content_type: "text".to_string(),
-                text: Some(contents.clone()),
-                data: None,
-                mime_type: None,
-                annotations: None,
-            }],
-            is_error: Some(false),
-            structured_content: Some(
-                serde_json::to_value(serde_json::json!({
-                    "file_path": file_path,
-                    "contents": contents
-                }))
+    // 目录、设备文件与命名管道等没有确定的内容，读取可能无休止或阻塞，判断类型前就拒绝。
+    // 打开前先按路径检查，避免打开命名管道时阻塞；打开后再检查句柄，之后只通过该句柄读取，
+    // 检查与打开之间路径被替换为其他文件时同样拒绝
+    let not_regular = || ToolError::InvalidArguments(format!("{} 不是普通文件", args.file_path));
+    let checked = tokio::fs::metadata(&resolved).await.map_err(read_error)?;
+    if !checked.is_file() {
+        return Err(not_regular());
+    }
+    let mut file = File::open(&resolved).await.map_err(read_error)?;
+    let metadata = file.metadata().await.map_err(read_error)?;
+    if !metadata.is_file() || !same_file(&checked, &metadata) {
+        return Err(not_regular());
+    }
+    let file_size = metadata.len();
+    let kind = sniff(&resolved, &mut file).await.map_err(read_error)?;
+    if kind.binary {
+        return read_binary(&args, &resolved, &mut file, &kind, file_size, max_bytes)
+            .await
+            .map_err(read_error);
+    }
+
+    let window = match args.mode {
+        ReadMode::Lines => {
+            let end = args.limit.map(|limit| offset.saturating_add(limit));
+            scan_lines(&mut file, offset, end, max_bytes as usize, args.count_lines).await
+        }
+        ReadMode::Head => {
+            let end = args.limit.unwrap_or(DEFAULT_EDGE_LINES);
+            scan_lines(
+                &mut file,
+                0,
+                Some(end),
+                max_bytes as usize,
+                args.count_lines,
+            )
+            .await
+        }
+        ReadMode::Tail => {
+            let lines = args.limit.unwrap_or(DEFAULT_EDGE_LINES);
+            read_tail(&mut file, lines, max_bytes as usize, args.count_lines).await
+        }
+        ReadMode::Bytes => read_bytes(&mut file, offset, args.limit, max_bytes, !kind.latin1).await,
+    }
+    .map_err(read_error)?;
+
+    let mut text = if kind.latin1 {
+        window.data.iter().map(|&byte| char::from(byte)).collect()
+    } else {
+        String::from_utf8_lossy(&window.data).into_owned()
+    };
+    if let Some(note) = paging_note(args.mode, &window, file_size) {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&note);
+    }
+
+    Ok(ToolCallResult {
+        content: vec![ToolContent {
+            content_type: "text".to_string(),
+            text: Some(text),
+            data: None,
+            mime_type: None,
+            annotations: None,
+            resource: None,
+        }],
+        is_error: Some(false),
+        meta: None,
+        structured_content: Some(serde_json::json!({
+            "file_path": args.file_path,
+            "mime_type": kind.mime_type,
+            "binary": false,
+            "encoding": if kind.latin1 { "latin-1" } else { "utf-8" },
+            "mode": args.mode,
+            "offset": window.offset,
+            "file_size": file_size,
+            "total_lines": window.total_lines,
+            "lines_returned": window.lines_returned,
+            "bytes_returned": window.data.len(),
+            "truncated": window.truncated,
+            "next_offset": window.next_offset,
+        })),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        server::state::{self, ServerState},
+        tools::registry::{ToolRegistry, builtin_tools},
+    };
+
+    async fn read_file(arguments: serde_json::Value) -> Result<ToolCallResult, ToolError> {
+        // 默认配置：不限制根目录，只有默认的禁止规则
+        let state = ServerState::new(
+            ToolRegistry::new(builtin_tools()),
+            serde_yaml::from_str("listen_port: 3000").unwrap(),
+        );
+        state::scope(
+            state,
+            handle_read_file_tool(serde_json::from_value(arguments).unwrap()),
+        )
+        .await
+    }
+
+    /// 测试用的临时文件，结束时删除
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, contents: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "local_mcp_read_file_{}_{}",
+                name,
+                std::process::id()
+            ));
+            std::fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+
+        async fn open(&self) -> File {
+            File::open(&self.0).await.unwrap()
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn numbered_lines(count: u64) -> String {
+        (0..count).map(|line| format!("line {}\n", line)).collect()
+    }
+
+    #[tokio::test]
+    async fn head_stops_at_window_end() {
+        let file = TempFile::new("head", numbered_lines(100).as_bytes());
+        let window = scan_lines(&mut file.open().await, 0, Some(3), 1024, false)
+            .await
+            .unwrap();
+        assert_eq!(window.data, b"line 0\nline 1\nline 2\n");
+        assert_eq!(window.total_lines, None);
+        assert_eq!(window.next_offset, Some(3));
+
+        let window = scan_lines(&mut file.open().await, 0, Some(3), 1024, true)
+            .await
+            .unwrap();
+        assert_eq!(window.total_lines, Some(100));
+        assert_eq!(window.next_offset, Some(3));
+    }
+
+    #[tokio::test]
+    async fn lines_reaching_end_of_file_report_total() {
+        let file = TempFile::new("lines", b"a\nb\nc");
+        let window = scan_lines(&mut file.open().await, 1, None, 1024, false)
+            .await
+            .unwrap();
+        assert_eq!(window.data, b"b\nc");
+        assert_eq!(window.offset, Some(1));
+        assert_eq!(window.total_lines, Some(3));
+        assert_eq!(window.next_offset, None);
+
+        let window = scan_lines(&mut file.open().await, 1, Some(3), 1024, false)
+            .await
+            .unwrap();
+        assert_eq!(window.next_offset, None);
+    }
+
+    #[tokio::test]
+    async fn truncated_lines_keep_whole_lines() {
+        let file = TempFile::new("truncated", numbered_lines(100).as_bytes());
+        let window = scan_lines(&mut file.open().await, 0, None, 10, false)
+            .await
+            .unwrap();
+        assert_eq!(window.data, b"line 0\n");
+        assert!(window.truncated);
+        assert!(!window.partial_line);
+        assert_eq!(window.next_offset, Some(1));
+    }
+
+    #[tokio::test]
+    async fn tail_reads_last_lines_from_end() {
+        let contents = numbered_lines(100_000);
+        let file = TempFile::new("tail", contents.as_bytes());
+        let window = read_tail(&mut file.open().await, 2, 1024, false)
+            .await
+            .unwrap();
+        assert_eq!(window.data, b"line 99998\nline 99999\n");
+        assert_eq!(window.lines_returned, Some(2));
+        assert_eq!(window.total_lines, None);
+        assert_eq!(window.offset, None);
+        assert_eq!(window.next_offset, None);
+
+        let window = read_tail(&mut file.open().await, 2, 1024, true)
+            .await
+            .unwrap();
+        assert_eq!(window.total_lines, Some(100_000));
+        assert_eq!(window.offset, Some(99_998));
+    }
+
+    #[tokio::test]
+    async fn tail_of_small_file_knows_total() {
+        let file = TempFile::new("tail_small", b"a\nb\nc");
+        let window = read_tail(&mut file.open().await, 2, 1024, false)
+            .await
+            .unwrap();
+        assert_eq!(window.data, b"b\nc");
+        assert_eq!(window.total_lines, Some(3));
+        assert_eq!(window.offset, Some(1));
+
+        let window = read_tail(&mut file.open().await, 10, 1024, false)
+            .await
+            .unwrap();
+        assert_eq!(window.data, b"a\nb\nc");
+        assert_eq!(window.offset, Some(0));
+
+        let empty = TempFile::new("tail_empty", b"");
+        let window = read_tail(&mut empty.open().await, 10, 1024, false)
+            .await
+            .unwrap();
+        assert!(window.data.is_empty());
+        assert_eq!(window.total_lines, Some(0));
+    }
+
+    #[tokio::test]
+    async fn truncated_tail_keeps_lines_nearest_end() {
+        let file = TempFile::new("tail_truncated", numbered_lines(100).as_bytes());
+        let window = read_tail(&mut file.open().await, 10, 20, false)
+            .await
+            .unwrap();
+        assert_eq!(window.data, b"line 98\nline 99\n");
+        assert!(window.truncated);
+        assert!(!window.partial_line);
+
+        let long_line = TempFile::new("tail_long", "é".repeat(100).as_bytes());
+        let window = read_tail(&mut long_line.open().await, 1, 11, false)
+            .await
+            .unwrap();
+        assert!(window.partial_line);
+        assert_eq!(String::from_utf8(window.data).unwrap(), "é".repeat(5));
+    }
+
+    #[tokio::test]
+    async fn text_bytes_do_not_split_characters() {
+        // "aé" 与 "中" 分别占 3 字节
+        let file = TempFile::new("bytes_utf8", "aé中".as_bytes());
+        let window = read_bytes(&mut file.open().await, 0, None, 2, true)
+            .await
+            .unwrap();
+        assert_eq!(window.data, b"a");
+        assert_eq!(window.next_offset, Some(1));
+
+        let window = read_bytes(&mut file.open().await, 1, None, 4, true)
+            .await
+            .unwrap();
+        assert_eq!(window.data, "é".as_bytes());
+        assert_eq!(window.next_offset, Some(3));
+
+        // offset 落在字符中间时从下一个完整字符开始
+        let window = read_bytes(&mut file.open().await, 2, None, 1024, true)
+            .await
+            .unwrap();
+        assert_eq!(window.offset, Some(3));
+        assert_eq!(window.data, "中".as_bytes());
+        assert_eq!(window.next_offset, None);
+
+        // 窗口容纳不下一个字符时仍然前进
+        let window = read_bytes(&mut file.open().await, 3, None, 1, true)
+            .await
+            .unwrap();
+        assert_eq!(window.data.len(), 1);
+        assert_eq!(window.next_offset, Some(4));
+
+        let window = read_bytes(&mut file.open().await, 0, None, 2, false)
+            .await
+            .unwrap();
+        assert_eq!(window.data.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn structured_content_has_no_contents() {
+        let file = TempFile::new("structured", b"hello\n");
+        let result = read_file(json!({"file_path": file.0})).await.unwrap();
+        assert_eq!(result.content[0].text.as_deref(), Some("hello\n"));
+        let structured = result.structured_content.unwrap();
+        assert!(structured.get("contents").is_none());
+        assert_eq!(structured["bytes_returned"], 6);
+    }
+
+    #[tokio::test]
+    async fn rejects_non_regular_files() {
+        let dir = std::env::temp_dir();
+        let result = read_file(json!({"file_path": dir})).await;
+        assert!(matches!(result, Err(ToolError::InvalidArguments(_))));
+
+        #[cfg(unix)]
+        for mode in ["lines", "bytes", "head", "tail"] {
+            let result = read_file(json!({"file_path": "/dev/zero", "mode": mode})).await;
+            assert!(matches!(result, Err(ToolError::InvalidArguments(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn sniff_detects_binary_by_content() {
+        let sniff_file = |name: &str, contents: &[u8]| {
+            let file = TempFile::new(name, contents);
+            async move { sniff(&file.0, &mut file.open().await).await.unwrap() }
+        };
+
+        // 文件头在多字节字符中间截断
+        let mut utf8 = "中".repeat(SNIFF_BYTES as usize / 3).into_bytes();
+        utf8.extend_from_slice("中".as_bytes());
+        let kind = sniff_file("utf8.txt", &utf8).await;
+        assert!(!kind.binary && !kind.latin1);
+
+        let kind = sniff_file("latin1.txt", b"caf\xe9 cr\xe8me\n").await;
+        assert!(!kind.binary && kind.latin1);
+
+        let kind = sniff_file("nul", b"abc\0def").await;
+        assert!(kind.binary);
+
+        let kind = sniff_file("archive", b"PK\x03\x04\x14\x00\x08\x00").await;
+        assert!(kind.binary);
+        assert_eq!(kind.mime_type, "application/zip");
+    }
+
+    #[tokio::test]
+    async fn latin1_text_is_decoded() {
+        let file = TempFile::new("latin1_read", b"caf\xe9\n");
+        let result = read_file(json!({"file_path": file.0})).await.unwrap();
+        assert_eq!(result.content[0].text.as_deref(), Some("café\n"));
+        assert_eq!(result.structured_content.unwrap()["encoding"], "latin-1");
+    }
+
+    #[tokio::test]
+    async fn read_whole_stops_at_limit() {
+        let file = TempFile::new("whole", b"0123456789");
+        assert_eq!(
+            read_whole(&mut file.open().await, 10)
+                .await
+                .unwrap()
                 .unwrap(),
-            ),
-        },
-        Err(e) => ToolCallResult {
-            content: vec![ToolContent {
-                content_type: "text".to_string(),
-                text: Some(format!("读取文件失败: {}", e)),
-                data: None,
-                mime_type: None,
-                annotations: None,
-            }],
-            is_error: Some(true),
-            structured_content: Some(serde_json::to_value(format!("读取文件失败: {}", e)).unwrap()),
-        },
+            b"0123456789"
+        );
+        assert!(
+            read_whole(&mut file.open().await, 9)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn images_respect_max_bytes() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.resize(2048, 0);
+        let file = TempFile::new("image.png", &png);
+
+        let result = read_file(json!({"file_path": file.0})).await.unwrap();
+        assert_eq!(result.content[0].content_type, "image");
+
+        let result = read_file(json!({"file_path": file.0, "max_bytes": 1024}))
+            .await
+            .unwrap();
+        assert_eq!(result.content[0].content_type, "text");
+        let structured = result.structured_content.unwrap();
+        assert_eq!(structured["encoding"], "hex");
+        assert_eq!(structured["mime_type"], "image/png");
     }
 }
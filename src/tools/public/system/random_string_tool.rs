@@ -1,11 +1,18 @@
-use crate::tools::tool_dto::{ToolCallResult, ToolContent};
+use crate::mcp_tool;
+use crate::tools::{
+    error::ToolError,
+    tool_dto::{ToolCallResult, ToolContent},
+};
 use rand::Rng;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 pub struct RandomStringParams {
+    /// 随机字符串的长度,默认16位的大小写字母+数字
     #[serde(default = "default_length")]
     pub length: usize,
+    /// 是否包含特殊字符,默认不包含
     #[serde(default = "default_include_special")]
     pub include_special: bool,
 }
@@ -57,51 +64,34 @@ fn generate_random_string(length: usize, include_special: Option<bool>) -> Strin
         .collect()
 }
 
-pub fn handle_random_string_tool(args_json: Option<serde_json::Value>) -> ToolCallResult {
-    let params = serde_json::from_value::<RandomStringParams>(match args_json {
-        Some(args) => args,
-        None => {
-            return ToolCallResult {
-                content: vec![ToolContent {
-                    content_type: "text".to_string(),
-                    text: Some("缺少参数".to_string()),
-                    data: None,
-                    mime_type: None,
-                    annotations: None,
-                }],
-                is_error: Some(true),
-                structured_content: Some(serde_json::to_value(String::from("缺少参数")).unwrap()),
-            };
-        }
-    })
-    .unwrap();
-
+#[mcp_tool(
+    name = "random string",
+    title = "随机字符串",
+    description = "生成随机字符串",
+    read_only,
+    non_deterministic
+)]
+pub fn handle_random_string_tool(params: RandomStringParams) -> Result<ToolCallResult, ToolError> {
     // 验证参数
     if params.length < 1 || params.length > 4096 {
-        return ToolCallResult {
-            content: vec![ToolContent {
-                content_type: "text".to_string(),
-                text: Some("字符串长度必须为1到4096!".to_string()),
-                data: None,
-                mime_type: None,
-                annotations: None,
-            }],
-            is_error: Some(true),
-            structured_content: None,
-        };
+        return Err(ToolError::InvalidArguments(
+            "字符串长度必须为1到4096!".to_string(),
+        ));
     }
 
     let random_string = generate_random_string(params.length, Some(params.include_special));
 
-    ToolCallResult {
+    Ok(ToolCallResult {
         content: vec![ToolContent {
             content_type: "text".to_string(),
             text: Some(random_string),
             data: None,
             mime_type: None,
             annotations: None,
+            resource: None,
         }],
         is_error: Some(false),
+        meta: None,
         structured_content: None,
-    }
+    })
 }
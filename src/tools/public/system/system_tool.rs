@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::env;
 
+use crate::mcp_tool;
 use crate::tools::tool_dto::{ToolCallResult, ToolContent};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,7 +20,13 @@ fn get_system_type() -> SystemInfo {
 /**
  * 获取系统类型
  */
-pub fn handle_get_system_type(_args: Option<serde_json::Value>) -> ToolCallResult {
+#[mcp_tool(
+    name = "get_system_type",
+    title = "系统类型信息",
+    description = "获取当前运行系统的类型信息,包括操作系统、架构",
+    read_only
+)]
+pub fn handle_get_system_type() -> ToolCallResult {
     let system_info = get_system_type();
     let result_text = serde_json::to_string_pretty(&system_info)
         .unwrap_or_else(|_| "Failed to serialize system information".to_string());
@@ -31,8 +38,10 @@ pub fn handle_get_system_type(_args: Option<serde_json::Value>) -> ToolCallResul
             data: None,
             mime_type: None,
             annotations: None,
+            resource: None,
         }],
         is_error: Some(false),
+        meta: None,
         structured_content: Some(serde_json::to_value(system_info).unwrap_or_default()),
     }
 }
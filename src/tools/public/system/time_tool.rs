@@ -1,5 +1,6 @@
 use chrono::Local;
 
+use crate::mcp_tool;
 use crate::tools::tool_dto::{ToolCallResult, ToolContent};
 
 fn get_current_time() -> String {
@@ -9,7 +10,14 @@ fn get_current_time() -> String {
 /**
  * 获取当前时间
  */
-pub fn handle_get_current_time(_args: Option<serde_json::Value>) -> ToolCallResult {
+#[mcp_tool(
+    name = "get_current_time",
+    title = "当前时间",
+    description = "获取当前时间",
+    read_only,
+    non_deterministic
+)]
+pub fn handle_get_current_time() -> ToolCallResult {
     let result_text = get_current_time();
     ToolCallResult {
         content: vec![ToolContent {
@@ -18,8 +26,10 @@ pub fn handle_get_current_time(_args: Option<serde_json::Value>) -> ToolCallResu
             data: None,
             mime_type: None,
             annotations: None,
+            resource: None,
         }],
         is_error: Some(false),
+        meta: None,
         structured_content: Some(serde_json::json!({
             "timestamp": result_text,
             "format": "YYYY-MM-DD HH:MM:SS"
@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{config::config::RateLimitConfig, middleware::auth::AuthIdentity};
+
+/// 超出调用频率限制时返回的JSON-RPC错误码
+pub const RATE_LIMITED_CODE: i32 = -32029;
+
+/// 计数桶数量上限，超出时清理长时间未使用的桶
+const MAX_BUCKETS: usize = 10_000;
+const BUCKET_IDLE_TTL: Duration = Duration::from_secs(600);
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// 按 (调用方, 工具名) 分别计数的令牌桶
+static BUCKETS: LazyLock<Mutex<HashMap<(String, String), Bucket>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 新建会话在计数桶中使用的名称，工具名不会为空
+const NEW_SESSION: &str = "";
+
+/// 限流计数的调用方：优先使用认证身份，其次是对端IP，使客户端不能靠重新 initialize 换一个新桶；
+/// 没有IP（Unix 域套接字）时使用服务端分配的会话ID，都没有时共用同一个桶
+pub fn client_key(
+    identity: Option<&AuthIdentity>,
+    session_id: Option<&str>,
+    peer: Option<IpAddr>,
+) -> String {
+    match (identity, peer, session_id) {
+        (Some(identity), _, _) => format!("identity:{}", identity.name),
+        (None, Some(peer), _) => format!("ip:{}", peer),
+        (None, None, Some(session_id)) => format!("session:{}", session_id),
+        (None, None, None) => "anonymous".to_string(),
+    }
+}
+
+/// 新建会话（HTTP initialize 与 WebSocket 连接）消耗一次额度，按认证身份或对端IP计数
+pub fn check_new_session(
+    identity: Option<&AuthIdentity>,
+    peer: Option<IpAddr>,
+    limit: &RateLimitConfig,
+) -> Result<(), Duration> {
+    check(&client_key(identity, None, peer), NEW_SESSION, limit)
+}
+
+/// 消耗一次调用额度；额度不足时返回需要等待的时间
+pub fn check(client: &str, tool: &str, limit: &RateLimitConfig) -> Result<(), Duration> {
+    let capacity = f64::from(limit.burst.unwrap_or(limit.per_minute));
+    let refill_per_sec = f64::from(limit.per_minute) / 60.0;
+    let now = Instant::now();
+
+    let mut buckets = BUCKETS.lock().unwrap();
+    if buckets.len() >= MAX_BUCKETS {
+        buckets.retain(|_, bucket| now.duration_since(bucket.updated) < BUCKET_IDLE_TTL);
+    }
+
+    let bucket = buckets
+        .entry((client.to_string(), tool.to_string()))
+        .or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+    // 配置热加载后容量可能变小，按当前容量截断
+    bucket.tokens = (bucket.tokens
+        + now.duration_since(bucket.updated).as_secs_f64() * refill_per_sec)
+        .min(capacity);
+    bucket.updated = now;
+
+    if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
+        Ok(())
+    } else {
+        Err(Duration::from_secs_f64(
+            (1.0 - bucket.tokens) / refill_per_sec,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(per_minute: u32, burst: Option<u32>) -> RateLimitConfig {
+        RateLimitConfig { per_minute, burst }
+    }
+
+    #[test]
+    fn client_key_prefers_identity_then_peer_then_session() {
+        let identity = AuthIdentity {
+            name: "ci".to_string(),
+            allowed_tools: None,
+        };
+        let peer = Some("192.0.2.7".parse().unwrap());
+        assert_eq!(client_key(Some(&identity), Some("s1"), peer), "identity:ci");
+        assert_eq!(client_key(None, Some("s1"), peer), "ip:192.0.2.7");
+        assert_eq!(client_key(None, None, peer), "ip:192.0.2.7");
+        assert_eq!(client_key(None, Some("s1"), None), "session:s1");
+        assert_eq!(client_key(None, None, None), "anonymous");
+    }
+
+    #[test]
+    fn burst_is_exhausted_then_retry_after_reported() {
+        let limit = limit(60, Some(3));
+        for _ in 0..3 {
+            assert!(check("test:burst", "tool", &limit).is_ok());
+        }
+        let retry_after = check("test:burst", "tool", &limit).unwrap_err();
+        // 每秒补充一次额度
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn buckets_are_separate_per_client_and_tool() {
+        let limit = limit(60, Some(1));
+        assert!(check("test:a", "tool", &limit).is_ok());
+        assert!(check("test:a", "tool", &limit).is_err());
+        assert!(check("test:b", "tool", &limit).is_ok());
+        assert!(check("test:a", "other", &limit).is_ok());
+    }
+
+    #[test]
+    fn burst_defaults_to_per_minute() {
+        let limit = limit(5, None);
+        for _ in 0..5 {
+            assert!(check("test:default", "tool", &limit).is_ok());
+        }
+        assert!(check("test:default", "tool", &limit).is_err());
+    }
+
+    #[test]
+    fn tokens_refill_over_time() {
+        let limit = limit(60_000, Some(1));
+        assert!(check("test:refill", "tool", &limit).is_ok());
+        assert!(check("test:refill", "tool", &limit).is_err());
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(check("test:refill", "tool", &limit).is_ok());
+    }
+
+    #[test]
+    fn smaller_capacity_after_reload_truncates_bucket() {
+        assert!(check("test:shrink", "tool", &limit(60, Some(10))).is_ok());
+        let smaller = limit(60, Some(2));
+        assert!(check("test:shrink", "tool", &smaller).is_ok());
+        assert!(check("test:shrink", "tool", &smaller).is_ok());
+        assert!(check("test:shrink", "tool", &smaller).is_err());
+    }
+}
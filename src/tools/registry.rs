@@ -0,0 +1,253 @@
+use std::{collections::HashMap, future::Future, ops::Deref, pin::Pin, sync::Arc};
+
+use schemars::{JsonSchema, generate::SchemaSettings};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::server::state::{self, ServerState};
+use crate::tools::{
+    context::ToolContext,
+    error::ToolError,
+    public::{
+        network::{ping_tool::handle_ping_tool_entry, read_ip_tool::handle_read_ip_tool_entry},
+        system::{
+            file::{
+                list_files_tool::handle_list_files_tool_entry,
+                read_file_tool::handle_read_file_tool_entry,
+            },
+            random_string_tool::handle_random_string_tool_entry,
+            system_tool::handle_get_system_type_entry,
+            time_tool::handle_get_current_time_entry,
+        },
+    },
+    tool_dto::{Tool, ToolCallResult, ToolInputSchema},
+};
+
+pub type ToolFuture = Pin<Box<dyn Future<Output = ToolCallResult> + Send>>;
+pub type ToolHandler = Arc<dyn Fn(ToolContext, Option<Value>) -> ToolFuture + Send + Sync>;
+
+/// 注册表中的单个工具：定义与处理函数
+#[derive(Clone)]
+pub struct ToolEntry {
+    pub tool: Tool,
+    pub handler: ToolHandler,
+    /// 相同参数是否总是返回相同结果，结果随时间或随机变化的工具为 false
+    pub deterministic: bool,
+    /// 值为文件路径的参数，审批规则按路径策略解析后的真实路径匹配
+    pub path_arguments: Vec<String>,
+}
+
+impl ToolEntry {
+    /// 不需要调用上下文的工具
+    pub fn new<F, Fut>(tool: Tool, handler: F) -> Self
+    where
+        F: Fn(Option<Value>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ToolCallResult> + Send + 'static,
+    {
+        Self {
+            tool,
+            handler: Arc::new(move |_, args| Box::pin(handler(args))),
+            deterministic: true,
+            path_arguments: Vec::new(),
+        }
+    }
+
+    /// 需要会话状态、取消信号或进度上报的工具
+    pub fn with_context<F, Fut>(tool: Tool, handler: F) -> Self
+    where
+        F: Fn(ToolContext, Option<Value>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ToolCallResult> + Send + 'static,
+    {
+        Self {
+            tool,
+            handler: Arc::new(move |ctx, args| Box::pin(handler(ctx, args))),
+            deterministic: true,
+            path_arguments: Vec::new(),
+        }
+    }
+
+    /// 标记结果随时间或随机变化（如当前时间、随机字符串），这类工具的结果不缓存
+    pub fn non_deterministic(mut self) -> Self {
+        self.deterministic = false;
+        self
+    }
+
+    /// 标记值为文件路径的参数
+    pub fn path_argument(mut self, name: &str) -> Self {
+        self.path_arguments.push(name.to_string());
+        self
+    }
+
+    /// 工具是否标注为只读（annotations.readOnlyHint）
+    pub fn read_only(&self) -> bool {
+        self.tool
+            .annotations
+            .as_ref()
+            .and_then(|a| a.get("readOnlyHint"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// 只读且结果确定的工具才允许缓存结果
+    pub fn cacheable(&self) -> bool {
+        self.read_only() && self.deterministic
+    }
+}
+
+/// 工具注册表，按注册顺序返回工具列表
+pub struct ToolRegistry {
+    entries: Vec<ToolEntry>,
+    index: HashMap<String, usize>,
+}
+
+impl ToolRegistry {
+    pub fn new(entries: Vec<ToolEntry>) -> Self {
+        let index = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.tool.name.clone(), i))
+            .collect();
+        Self { entries, index }
+    }
+
+    /// 注册的工具总数
+    pub fn count(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ToolEntry> {
+        self.index.get(name).map(|i| &self.entries[*i])
+    }
+
+    /// 按注册顺序返回工具名
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.tool.name.as_str())
+    }
+
+    /// 按配置返回已启用的工具定义，参数默认值写入 schema 的 default 字段
+    pub fn list(&self, config: &Config) -> Vec<Tool> {
+        self.entries
+            .iter()
+            .filter(|entry| config.tool_enabled(&entry.tool.name))
+            .map(|entry| {
+                let mut tool = entry.tool.clone();
+                if let Some(tool_config) = config.tools.get(&tool.name)
+                    && let Some(Value::Object(properties)) = &mut tool.input_schema.properties
+                {
+                    for (key, value) in &tool_config.defaults {
+                        if let Some(Value::Object(property)) = properties.get_mut(key) {
+                            property.insert("default".to_string(), value.clone());
+                        }
+                    }
+                    // 配置了默认值的参数调用方可以不传
+                    if let Some(required) = &mut tool.input_schema.required {
+                        required.retain(|key| !tool_config.defaults.contains_key(key));
+                    }
+                }
+                tool
+            })
+            .collect()
+    }
+}
+
+/// 将配置中的参数默认值合并到调用参数，调用方传入的参数优先
+pub fn apply_defaults(
+    args: Option<Value>,
+    defaults: &serde_json::Map<String, Value>,
+) -> Option<Value> {
+    if defaults.is_empty() {
+        return args;
+    }
+    match args {
+        None | Some(Value::Null) => Some(Value::Object(defaults.clone())),
+        Some(Value::Object(mut args)) => {
+            for (key, value) in defaults {
+                args.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+            Some(Value::Object(args))
+        }
+        // 参数不是对象时交给工具自行报错
+        Some(other) => Some(other),
+    }
+}
+
+/// 由参数类型生成工具的输入 schema，供 `#[mcp_tool]` 使用
+pub fn input_schema_for<T: JsonSchema>() -> ToolInputSchema {
+    let mut settings = SchemaSettings::draft07();
+    settings.inline_subschemas = true;
+    let mut schema = settings.into_generator().into_root_schema_for::<T>();
+    let mut properties = schema
+        .remove("properties")
+        .unwrap_or_else(|| Value::Object(Default::default()));
+    // 可选参数已由 required 表达，去掉 Option 生成的 null 类型
+    if let Value::Object(properties) = &mut properties {
+        for property in properties.values_mut() {
+            if let Some(Value::Array(types)) = property.get_mut("type") {
+                types.retain(|t| t != "null");
+                if types.len() == 1 {
+                    let single = types.remove(0);
+                    property["type"] = single;
+                }
+            }
+        }
+    }
+    ToolInputSchema {
+        schema_type: "object".to_string(),
+        properties: Some(properties),
+        required: schema
+            .remove("required")
+            .and_then(|required| serde_json::from_value(required).ok()),
+    }
+}
+
+/// 解析工具参数，未传参数时按空对象处理
+pub fn parse_arguments<T: DeserializeOwned>(args: Option<Value>) -> Result<T, ToolError> {
+    let args = match args {
+        None | Some(Value::Null) => Value::Object(Default::default()),
+        Some(args) => args,
+    };
+    serde_json::from_value(args).map_err(|e| ToolError::InvalidArguments(e.to_string()))
+}
+
+/// 由注解名称生成工具的 annotations，如 readOnlyHint；没有注解时返回 None
+pub fn tool_annotations(hints: &[&str]) -> Option<Value> {
+    if hints.is_empty() {
+        return None;
+    }
+    Some(Value::Object(
+        hints
+            .iter()
+            .map(|hint| (hint.to_string(), Value::Bool(true)))
+            .collect(),
+    ))
+}
+
+/// 服务器内置的全部工具
+pub fn builtin_tools() -> Vec<ToolEntry> {
+    vec![
+        handle_get_system_type_entry(),
+        handle_get_current_time_entry(),
+        handle_ping_tool_entry(),
+        handle_read_ip_tool_entry(),
+        handle_read_file_tool_entry(),
+        handle_list_files_tool_entry(),
+        handle_random_string_tool_entry(),
+    ]
+}
+
+/// 当前请求所属路由的工具注册表
+pub struct CurrentRegistry(Arc<ServerState>);
+
+impl Deref for CurrentRegistry {
+    type Target = ToolRegistry;
+
+    fn deref(&self) -> &ToolRegistry {
+        &self.0.registry
+    }
+}
+
+/// 获取当前请求所属路由的工具注册表
+pub fn registry() -> CurrentRegistry {
+    CurrentRegistry(state::current())
+}
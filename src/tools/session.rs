@@ -0,0 +1,201 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::middleware::auth::AuthIdentity;
+
+/// MCP 会话ID请求/响应头
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+/// 会话数量上限，达到时先清理长时间未使用的会话，仍然已满则拒绝创建
+const MAX_SESSIONS: usize = 10_000;
+const SESSION_IDLE_TTL: Duration = Duration::from_secs(3600);
+
+/// 生成新的会话ID（128位随机数的十六进制表示）
+fn new_session_id() -> String {
+    let bytes: [u8; 16] = rand::thread_rng().r#gen();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 客户端在 initialize 中提供的 clientInfo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// 单个会话的状态：客户端信息与工具保存的键值数据
+#[derive(Default)]
+pub struct SessionState {
+    /// 创建会话的认证身份名称，未启用认证时为 None
+    owner: Option<String>,
+    client_info: RwLock<Option<ClientInfo>>,
+    values: Mutex<HashMap<String, Box<dyn Any + Send + Sync>>>,
+    last_used: Mutex<Option<Instant>>,
+}
+
+impl SessionState {
+    pub fn client_info(&self) -> Option<ClientInfo> {
+        self.client_info.read().unwrap().clone()
+    }
+
+    pub fn set_client_info(&self, client_info: ClientInfo) {
+        *self.client_info.write().unwrap() = Some(client_info);
+    }
+
+    /// 读取键对应的值，值的类型与 T 不一致时返回 None
+    pub fn get<T: Clone + 'static>(&self, key: &str) -> Option<T> {
+        self.values
+            .lock()
+            .unwrap()
+            .get(key)
+            .and_then(|value| value.downcast_ref::<T>())
+            .cloned()
+    }
+
+    pub fn insert<T: Send + Sync + 'static>(&self, key: &str, value: T) {
+        self.values
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), Box::new(value));
+    }
+
+    pub fn remove(&self, key: &str) {
+        self.values.lock().unwrap().remove(key);
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap() = Some(Instant::now());
+    }
+
+    fn idle_for(&self, now: Instant) -> Duration {
+        self.last_used
+            .lock()
+            .unwrap()
+            .map_or(Duration::ZERO, |last| now.duration_since(last))
+    }
+}
+
+static SESSIONS: LazyLock<Mutex<HashMap<String, Arc<SessionState>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// 会话表是否还能加入新会话，已满时先清理长时间未使用的会话
+fn make_room(sessions: &mut HashMap<String, Arc<SessionState>>, max: usize) -> bool {
+    if sessions.len() >= max {
+        let now = Instant::now();
+        sessions.retain(|_, state| state.idle_for(now) < SESSION_IDLE_TTL);
+    }
+    sessions.len() < max
+}
+
+/// 为认证身份创建会话并返回会话ID，在 initialize 或 WebSocket 连接建立时调用；会话数量已达上限时返回错误
+pub fn create(owner: Option<&AuthIdentity>) -> Result<String, String> {
+    let session_id = new_session_id();
+    let mut sessions = SESSIONS.lock().unwrap();
+    if !make_room(&mut sessions, MAX_SESSIONS) {
+        return Err(format!("会话数量已达上限 {}", MAX_SESSIONS));
+    }
+    let state = Arc::new(SessionState {
+        owner: owner.map(|identity| identity.name.clone()),
+        ..SessionState::default()
+    });
+    state.touch();
+    sessions.insert(session_id.clone(), state);
+    Ok(session_id)
+}
+
+/// 获取服务端分配的会话，未知或已清理的会话ID返回 None
+pub fn get(session_id: &str) -> Option<Arc<SessionState>> {
+    let state = SESSIONS.lock().unwrap().get(session_id).cloned()?;
+    state.touch();
+    Some(state)
+}
+
+/// 获取该认证身份创建的会话；会话不存在或属于其他身份时返回 None
+pub fn get_owned(session_id: &str, identity: Option<&AuthIdentity>) -> Option<Arc<SessionState>> {
+    get(session_id).filter(|state| state.owner.as_deref() == identity.map(|i| i.name.as_str()))
+}
+
+/// 当前的会话数量
+pub fn count() -> usize {
+    SESSIONS.lock().unwrap().len()
+}
+
+/// 会话结束（如 WebSocket 断开或客户端 DELETE）时释放会话状态
+pub fn remove_session(session_id: &str) {
+    SESSIONS.lock().unwrap().remove(session_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_issued_sessions_are_known() {
+        assert!(get("not-issued-by-the-server").is_none());
+
+        let session_id = create(None).unwrap();
+        assert_eq!(session_id.len(), 32);
+        let session = get(&session_id).expect("issued session");
+        session.insert("cwd", "/tmp".to_string());
+        assert_eq!(
+            get(&session_id).unwrap().get::<String>("cwd").as_deref(),
+            Some("/tmp")
+        );
+
+        remove_session(&session_id);
+        assert!(get(&session_id).is_none());
+    }
+
+    #[test]
+    fn session_ids_are_unique() {
+        let a = create(None).unwrap();
+        let b = create(None).unwrap();
+        assert_ne!(a, b);
+        remove_session(&a);
+        remove_session(&b);
+    }
+
+    #[test]
+    fn sessions_belong_to_their_creator() {
+        let identity = |name: &str| AuthIdentity {
+            name: name.to_string(),
+            allowed_tools: None,
+        };
+        let session_id = create(Some(&identity("a"))).unwrap();
+        assert!(get_owned(&session_id, Some(&identity("a"))).is_some());
+        assert!(get_owned(&session_id, Some(&identity("b"))).is_none());
+        assert!(get_owned(&session_id, None).is_none());
+        remove_session(&session_id);
+
+        let anonymous = create(None).unwrap();
+        assert!(get_owned(&anonymous, None).is_some());
+        assert!(get_owned(&anonymous, Some(&identity("a"))).is_none());
+        remove_session(&anonymous);
+    }
+
+    #[test]
+    fn full_table_keeps_active_sessions() {
+        let mut sessions = HashMap::new();
+        for index in 0..3 {
+            let state = Arc::new(SessionState::default());
+            state.touch();
+            sessions.insert(index.to_string(), state);
+        }
+        assert!(make_room(&mut sessions, 4));
+        assert!(!make_room(&mut sessions, 3));
+        assert_eq!(sessions.len(), 3);
+
+        // 长时间未使用的会话被清理后可以创建新会话
+        *sessions["0"].last_used.lock().unwrap() = Instant::now().checked_sub(SESSION_IDLE_TTL);
+        assert!(make_room(&mut sessions, 3));
+        assert_eq!(sessions.len(), 2);
+    }
+}
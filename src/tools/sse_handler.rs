@@ -1,13 +1,48 @@
 use axum::{
+    Extension,
     http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response, Sse, sse::Event},
 };
 use futures::stream;
 use std::{convert::Infallible, time::Duration};
+use tokio::sync::broadcast::error::RecvError;
 use tokio_stream::StreamExt as _;
 
+use crate::{
+    middleware::auth::AuthIdentity,
+    server::{
+        metrics::{GaugeGuard, metrics},
+        shutdown,
+    },
+    tools::{mcp_handler::request_session, notifier},
+};
+
+/// 将服务端通知转换为SSE消息事件，只转发广播通知与发给该会话的通知
+fn notification_stream(
+    session_id: Option<String>,
+) -> impl futures::Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(notifier::subscribe(), move |mut receiver| {
+        let session_id = session_id.clone();
+        async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(targeted) if targeted.is_for(session_id.as_deref()) => {
+                        let data =
+                            serde_json::to_string(&targeted.notification).unwrap_or_default();
+                        return Some((Ok(Event::default().data(data).event("message")), receiver));
+                    }
+                    Ok(_) => continue,
+                    // 客户端消费过慢时跳过积压的通知
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    })
+}
+
 /// SSE处理器
-pub async fn handle_sse(headers: HeaderMap) -> Response {
+pub async fn handle_sse(identity: Option<Extension<AuthIdentity>>, headers: HeaderMap) -> Response {
     // 检查Accept头是否包含text/event-stream（宽松检查，兼容LM Studio）
     let accept_header = headers
         .get(header::ACCEPT)
@@ -26,13 +61,18 @@ pub async fn handle_sse(headers: HeaderMap) -> Response {
             .unwrap();
     }
 
+    let session_id = match request_session(&headers, identity.as_ref().map(|i| &i.0)) {
+        Ok(session_id) => session_id,
+        Err(error) => return error.into_response(),
+    };
+
     // 首先发送初始化消息，然后保持连接
     let stream = stream::iter(vec![
         Ok::<Event, Infallible>(Event::default()
-            .data("{\"jsonrpc\":\"2.0\",\"id\":null,\"result\":{\"capabilities\":{\"tools\":{\"listChanged\":false}},\"protocolVersion\":\"2025-06-18\",\"serverInfo\":{\"name\":\"local_mcp_server\",\"version\":\"0.1.0\"}}}")
+            .data("{\"jsonrpc\":\"2.0\",\"id\":null,\"result\":{\"capabilities\":{\"tools\":{\"listChanged\":true}},\"protocolVersion\":\"2025-06-18\",\"serverInfo\":{\"name\":\"local_mcp_server\",\"version\":\"0.1.0\"}}}")
             .event("initialize"))
     ])
-    .chain(
+    .chain(stream::select(
         // 然后每30秒发送一次心跳
         stream::unfold((), |_| async {
             tokio::time::sleep(Duration::from_secs(30)).await;
@@ -42,9 +82,29 @@ pub async fn handle_sse(headers: HeaderMap) -> Response {
                     .event("heartbeat")),
                 (),
             ))
-        })
+        }),
+        // 同时转发服务端通知（如 tools/list_changed）；携带 Mcp-Session-Id 时还会收到该会话的调用进度
+        notification_stream(session_id),
+    ));
+
+    // 关闭时结束事件流，并告知客户端服务器即将退出
+    let stream = futures::StreamExt::take_until(stream, shutdown::token().cancelled_owned()).chain(
+        stream::once(async {
+            Ok::<Event, Infallible>(
+                Event::default()
+                    .data("{\"reason\":\"server shutting down\"}")
+                    .event("shutdown"),
+            )
+        }),
     );
 
+    // 连接计数随响应流一起释放
+    let connection = GaugeGuard::new(metrics().sse_connections.clone());
+    let stream = stream.map(move |event| {
+        let _ = &connection;
+        event
+    });
+
     let sse = Sse::new(stream).keep_alive(
         axum::response::sse::KeepAlive::new()
             .interval(Duration::from_secs(15))
@@ -63,9 +123,6 @@ pub async fn handle_sse(headers: HeaderMap) -> Response {
     response
         .headers_mut()
         .insert(header::CONNECTION, HeaderValue::from_static("keep-alive"));
-    response
-        .headers_mut()
-        .insert("Access-Control-Allow-Origin", HeaderValue::from_static("*"));
 
     response
 }
@@ -96,20 +96,26 @@ pub struct ToolCallParams {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arguments: Option<Value>,
+    /// 请求附加信息，如 progressToken
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 /// 工具调用响应结果
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ToolCallResult {
     pub content: Vec<ToolContent>,
     #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
     #[serde(rename = "structuredContent", skip_serializing_if = "Option::is_none")]
     pub structured_content: Option<Value>,
+    /// 附加信息，如结果缓存命中情况
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 /// 工具内容类型
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ToolContent {
     #[serde(rename = "type")]
     pub content_type: String,
@@ -121,9 +127,24 @@ pub struct ToolContent {
     pub mime_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub annotations: Option<ContentAnnotations>,
+    /// type 为 "resource" 时嵌入的资源
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource: Option<EmbeddedResource>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 嵌入资源，text 与 blob（base64）二选一
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmbeddedResource {
+    pub uri: String,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ContentAnnotations {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub audience: Option<Vec<String>>,
@@ -132,7 +153,7 @@ pub struct ContentAnnotations {
 }
 
 /// 通知消息
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Notification {
     pub jsonrpc: String,
     pub method: String,
@@ -0,0 +1,296 @@
+use axum::{
+    Extension,
+    extract::{
+        ConnectInfo,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
+    },
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use tokio::sync::{Semaphore, broadcast::error::RecvError, mpsc};
+use tokio_util::task::TaskTracker;
+use tracing::Instrument;
+
+use crate::{
+    config::config,
+    middleware::{
+        auth::{self, AuthIdentity},
+        origin,
+    },
+    server::{
+        metrics::{GaugeGuard, metrics},
+        peer::PeerAddr,
+        shutdown, state,
+    },
+    tools::{
+        client_request::{CLIENT_CHANNEL_KEY, ClientChannel, is_response},
+        mcp_handler::{check_new_session, dispatch_mcp_message, parse_error_response},
+        notifier, session,
+    },
+};
+
+/// 发往客户端的消息队列长度，客户端读取过慢时写入方等待
+const OUTBOUND_BUFFER: usize = 64;
+/// 每条连接同时处理的请求数上限，超出的请求直接返回错误
+const MAX_IN_FLIGHT_REQUESTS: usize = 32;
+
+/// WebSocket处理器，在一条全双工连接上承载双向JSON-RPC：客户端请求及其响应、服务端通知，
+/// 以及工具经 ToolContext::request 向客户端发起的请求
+pub async fn handle_ws(
+    identity: Option<Extension<AuthIdentity>>,
+    peer: Option<Extension<ConnectInfo<PeerAddr>>>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Response {
+    // 每条连接是一个新会话，与 initialize 共用新建会话的频率限制
+    let peer = peer.and_then(|Extension(ConnectInfo(peer))| peer.ip());
+    if let Err(retry_after) = check_new_session(identity.as_ref().map(|i| &i.0), peer) {
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, retry_after.as_secs() + 1)],
+            "Too many new sessions",
+        )
+            .into_response();
+    }
+    // 认证中间件已在升级时校验过凭据；配置可能热加载，之后每条消息都按当前配置重新校验来源与令牌
+    let credentials = Credentials {
+        origin: headers
+            .get(header::ORIGIN)
+            .map(|origin| origin.to_str().unwrap_or_default().to_string()),
+        token: auth::extract_token(&headers).map(str::to_string),
+    };
+    // 升级后的连接在独立任务中运行，需要带上路由状态；它也不受 axum 优雅关闭跟踪，由关闭流程单独等待
+    let state = state::current();
+    let owner = identity.map(|Extension(identity)| identity);
+    ws.on_upgrade(move |socket| {
+        shutdown::in_flight().track_future(state::scope(
+            state,
+            handle_socket(socket, credentials, owner, peer),
+        ))
+    })
+}
+
+/// 连接建立时携带的来源与令牌
+struct Credentials {
+    origin: Option<String>,
+    token: Option<String>,
+}
+
+impl Credentials {
+    /// 按当前配置校验来源并重新认证，返回调用方身份；不再有效时返回关闭原因
+    async fn recheck(&self) -> Result<Option<AuthIdentity>, &'static str> {
+        if let Some(origin) = &self.origin
+            && !origin::is_origin_allowed(origin)
+        {
+            return Err("origin no longer allowed");
+        }
+        let config = config::current();
+        let Some(auth_config) = &config.auth else {
+            return Ok(None);
+        };
+        let token = self.token.as_deref().ok_or("credentials no longer valid")?;
+        auth::authenticate(auth_config, token)
+            .await
+            .map(Some)
+            .map_err(|_| "credentials no longer valid")
+    }
+}
+
+async fn handle_socket(
+    mut socket: WebSocket,
+    credentials: Credentials,
+    owner: Option<AuthIdentity>,
+    peer: Option<IpAddr>,
+) {
+    // 每条连接是一个独立会话，会话数量已满时请客户端稍后重试
+    let session_id = match session::create(owner.as_ref()) {
+        Ok(session_id) => session_id,
+        Err(e) => {
+            tracing::warn!(error = %e, "Rejecting WebSocket connection");
+            let _ = socket
+                .send(Message::Close(Some(CloseFrame {
+                    code: close_code::AGAIN,
+                    reason: "too many sessions".into(),
+                })))
+                .await;
+            return;
+        }
+    };
+    let (mut sink, mut stream) = socket.split();
+    let _connection = GaugeGuard::new(metrics().ws_connections.clone());
+    let span = tracing::info_span!("mcp.ws", session_id = %session_id);
+    span.in_scope(|| tracing::debug!("WebSocket connected"));
+
+    // 所有发往客户端的消息（响应、服务端请求、通知）统一经由有界通道写出，客户端读取过慢时发送方等待
+    let (outbound_tx, mut outbound_rx) = mpsc::channel::<Message>(OUTBOUND_BUFFER);
+    let writer = tokio::spawn(async move {
+        while let Some(message) = outbound_rx.recv().await {
+            if sink.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    // 工具经该通道向客户端发起请求（ToolContext::request），客户端的响应在读取循环中按ID分发
+    let client = ClientChannel::new(outbound_tx.clone());
+    if let Some(session) = session::get(&session_id) {
+        session.insert(CLIENT_CHANNEL_KEY, client.clone());
+    }
+
+    // 转发服务端通知（如 tools/list_changed）及发给本会话的通知（如调用进度）
+    let mut notifications = notifier::subscribe();
+    let notification_tx = outbound_tx.clone();
+    let forward_session_id = session_id.clone();
+    let forwarder = tokio::spawn(async move {
+        loop {
+            match notifications.recv().await {
+                Ok(targeted) => {
+                    if !targeted.is_for(Some(&forward_session_id)) {
+                        continue;
+                    }
+                    let text = serde_json::to_string(&targeted.notification).unwrap_or_default();
+                    if notification_tx
+                        .send(Message::Text(text.into()))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    let shutdown = shutdown::token();
+    let frames = TaskTracker::new();
+    let in_flight = Arc::new(Semaphore::new(MAX_IN_FLIGHT_REQUESTS));
+    let mut revoked = None;
+    loop {
+        let message = tokio::select! {
+            message = stream.next() => match message {
+                Some(Ok(message)) => message,
+                _ => break,
+            },
+            // 关闭时不再读取新消息
+            _ = shutdown.cancelled() => break,
+        };
+        let payload = match message {
+            Message::Text(text) => text.as_str().as_bytes().to_vec(),
+            Message::Binary(bytes) => bytes.to_vec(),
+            Message::Close(_) => break,
+            // Ping/Pong 由 axum 自动处理
+            _ => continue,
+        };
+        let identity = match credentials.recheck().await {
+            Ok(identity) => identity,
+            Err(reason) => {
+                revoked = Some(reason);
+                break;
+            }
+        };
+
+        let reply = match serde_json::from_slice::<serde_json::Value>(&payload) {
+            // 客户端对服务端请求的响应直接交给等待的调用，不占用并发额度，工具等待响应时读取不会被阻塞
+            Ok(json_value) if is_response(&json_value) => {
+                if !client.resolve(&json_value) {
+                    span.in_scope(|| {
+                        tracing::debug!(id = %json_value["id"], "Ignoring response to unknown request");
+                    });
+                }
+                None
+            }
+            // 通知不会执行工具，直接处理，保证超出并发上限时 notifications/cancelled 仍然生效
+            Ok(json_value) if json_value.get("id").is_none() => {
+                dispatch_mcp_message(json_value, identity.as_ref(), Some(&session_id), peer)
+                    .instrument(span.clone())
+                    .await
+            }
+            Ok(json_value) => match in_flight.clone().try_acquire_owned() {
+                // 每条请求独立处理，避免慢工具阻塞同一连接上的其他请求
+                Ok(permit) => {
+                    let outbound_tx = outbound_tx.clone();
+                    let session_id = session_id.clone();
+                    frames.spawn(state::scope(
+                        state::current(),
+                        async move {
+                            let reply = dispatch_mcp_message(
+                                json_value,
+                                identity.as_ref(),
+                                Some(&session_id),
+                                peer,
+                            )
+                            .await;
+                            if let Some(reply) = reply {
+                                let _ = outbound_tx
+                                    .send(Message::Text(reply.to_string().into()))
+                                    .await;
+                            }
+                            drop(permit);
+                        }
+                        .instrument(span.clone()),
+                    ));
+                    None
+                }
+                Err(_) => Some(too_many_requests(json_value.get("id"))),
+            },
+            Err(_) => Some(parse_error_response()),
+        };
+        if let Some(reply) = reply
+            && outbound_tx
+                .send(Message::Text(reply.to_string().into()))
+                .await
+                .is_err()
+        {
+            break;
+        }
+    }
+
+    // 不再读取客户端的响应，等待中的服务端请求立即失败；再等待已收到的请求处理完成并回写，然后通知客户端关闭连接
+    client.close();
+    frames.close();
+    frames.wait().await;
+    let close = if shutdown.is_cancelled() {
+        Some((close_code::AWAY, "server shutting down"))
+    } else {
+        revoked.map(|reason| (close_code::POLICY, reason))
+    };
+    if let Some((code, reason)) = close {
+        let close = Message::Close(Some(CloseFrame {
+            code,
+            reason: reason.into(),
+        }));
+        let _ = tokio::time::timeout(Duration::from_secs(1), outbound_tx.send(close)).await;
+        session::remove_session(&session_id);
+        forwarder.abort();
+        drop(outbound_tx);
+        drop(client);
+        let _ = tokio::time::timeout(Duration::from_secs(1), writer).await;
+        span.in_scope(|| tracing::debug!(code, reason, "WebSocket closed"));
+        return;
+    }
+
+    span.in_scope(|| tracing::debug!("WebSocket disconnected"));
+    session::remove_session(&session_id);
+    forwarder.abort();
+    writer.abort();
+}
+
+/// 连接上进行中的请求达到上限时的错误响应
+fn too_many_requests(id: Option<&serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": -32000,
+            "message": format!(
+                "Too many concurrent requests on this connection (max {})",
+                MAX_IN_FLIGHT_REQUESTS
+            )
+        }
+    })
+}
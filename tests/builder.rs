@@ -0,0 +1,109 @@
+use axum::Router;
+use local_mcp_server::{McpServerBuilder, config::config::Config, config::loader::ConfigSource};
+use serde_json::{Value, json};
+
+fn config(yaml: &str) -> Config {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+async fn serve(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    url
+}
+
+async fn tool_names(url: &str) -> Vec<String> {
+    let list: Value = reqwest::Client::new()
+        .post(url)
+        .json(&json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    list["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn routers_in_one_process_keep_their_own_tools_and_config() {
+    let first = McpServerBuilder::new()
+        .only_tools(&["get_current_time"])
+        .build_router(config("listen_port: 3000"))
+        .unwrap();
+    let second = McpServerBuilder::new()
+        .only_tools(&["get_current_time", "get_system_type"])
+        .build_router(config(
+            "listen_port: 4000\ntools:\n  get_current_time:\n    enabled: false",
+        ))
+        .unwrap();
+
+    let first = serve(first).await;
+    let second = serve(second).await;
+    assert_eq!(tool_names(&first).await, ["get_current_time"]);
+    assert_eq!(tool_names(&second).await, ["get_system_type"]);
+}
+
+#[tokio::test]
+async fn invalid_config_does_not_prevent_a_later_build() {
+    let error = McpServerBuilder::empty()
+        .build_router(config("tools:\n  get_current_time:\n    enabled: false"))
+        .map(|_| ())
+        .unwrap_err();
+    assert!(error.contains("tools.get_current_time"), "{}", error);
+
+    let router = McpServerBuilder::new()
+        .only_tools(&["get_current_time"])
+        .build_router(config("tools:\n  get_current_time:\n    enabled: true"))
+        .unwrap();
+    assert_eq!(tool_names(&serve(router).await).await, ["get_current_time"]);
+}
+
+#[tokio::test]
+async fn run_returns_config_errors() {
+    let error = McpServerBuilder::new()
+        .run(ConfigSource::new("does-not-exist.yaml"))
+        .await
+        .unwrap_err();
+    assert!(error.contains("does-not-exist.yaml"), "{}", error);
+
+    let path = std::env::temp_dir().join(format!("builder-{}.yaml", std::process::id()));
+    std::fs::write(&path, "listen_port: 3000\ntool_timeout_ms: 0\n").unwrap();
+    let error = McpServerBuilder::new()
+        .run(ConfigSource::new(&path))
+        .await
+        .unwrap_err();
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.contains("tool_timeout_ms"), "{}", error);
+}
+
+#[tokio::test]
+async fn metrics_require_auth_when_configured() {
+    let router = McpServerBuilder::new()
+        .only_tools(&["get_current_time"])
+        .build_router(config(
+            "auth:\n  tokens:\n    - name: prometheus\n      token: scrape",
+        ))
+        .unwrap();
+    let url = format!("{}metrics", serve(router).await);
+    let client = reqwest::Client::new();
+
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    let response = client.get(&url).bearer_auth("scrape").send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert!(
+        response
+            .text()
+            .await
+            .unwrap()
+            .contains("mcp_requests_total")
+    );
+}
@@ -0,0 +1,256 @@
+//! `#[mcp_tool]` 宏生成的注册表条目：工具定义、参数解析与调用，以及通过 HTTP 端点调用
+
+use local_mcp_server::{
+    McpServerBuilder, ToolContext, ToolEntry, ToolError, mcp_tool, tools::tool_dto::ToolCallResult,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+fn text(text: String) -> ToolCallResult {
+    serde_json::from_value(json!({
+        "content": [{"type": "text", "text": text}],
+        "isError": false,
+    }))
+    .unwrap()
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct EchoArgs {
+    /// 要回显的文本
+    text: String,
+    /// 重复次数
+    #[serde(default = "one")]
+    times: usize,
+}
+
+fn one() -> usize {
+    1
+}
+
+#[mcp_tool(
+    name = "echo",
+    title = "回显",
+    description = "原样返回文本",
+    read_only,
+    idempotent
+)]
+async fn echo(args: EchoArgs) -> ToolCallResult {
+    text(args.text.repeat(args.times))
+}
+
+#[mcp_tool(description = "打招呼")]
+fn hello() -> ToolCallResult {
+    text("hello".to_string())
+}
+
+#[mcp_tool(description = "统计本会话的调用次数")]
+fn count(ctx: ToolContext) -> ToolCallResult {
+    let count = ctx.get::<u32>("count").unwrap_or(0) + 1;
+    ctx.insert("count", count);
+    text(count.to_string())
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct DivideArgs {
+    a: i64,
+    b: i64,
+}
+
+#[mcp_tool(name = "divide", description = "整数除法")]
+async fn divide(_ctx: ToolContext, args: DivideArgs) -> Result<ToolCallResult, ToolError> {
+    if args.b == 0 {
+        return Err(ToolError::InvalidArguments("除数不能为 0".to_string()));
+    }
+    Ok(text((args.a / args.b).to_string()))
+}
+
+#[mcp_tool(
+    name = "nonce",
+    description = "每次返回不同的值",
+    read_only,
+    non_deterministic
+)]
+fn nonce() -> ToolCallResult {
+    text(format!("{:?}", std::time::Instant::now()))
+}
+
+async fn call(entry: &ToolEntry, ctx: ToolContext, args: Value) -> ToolCallResult {
+    (entry.handler)(ctx, Some(args)).await
+}
+
+fn result_text(result: &ToolCallResult) -> &str {
+    result.content[0].text.as_deref().unwrap()
+}
+
+#[test]
+fn generates_tool_definition() {
+    let entry = echo_entry();
+    assert_eq!(entry.tool.name, "echo");
+    assert_eq!(entry.tool.title.as_deref(), Some("回显"));
+    assert_eq!(entry.tool.description, "原样返回文本");
+    assert_eq!(
+        entry.tool.annotations,
+        Some(json!({"readOnlyHint": true, "idempotentHint": true}))
+    );
+    assert!(entry.read_only());
+    assert!(entry.cacheable());
+
+    let schema = &entry.tool.input_schema;
+    assert_eq!(schema.schema_type, "object");
+    assert_eq!(schema.required, Some(vec!["text".to_string()]));
+    let properties = schema.properties.as_ref().unwrap();
+    assert_eq!(properties["text"]["type"], "string");
+    assert_eq!(properties["text"]["description"], "要回显的文本");
+    assert_eq!(properties["times"]["default"], 1);
+}
+
+#[test]
+fn name_defaults_to_function_name() {
+    let entry = hello_entry();
+    assert_eq!(entry.tool.name, "hello");
+    assert_eq!(entry.tool.title, None);
+    assert_eq!(entry.tool.annotations, None);
+    assert!(!entry.read_only());
+}
+
+#[test]
+fn non_deterministic_tools_are_not_cacheable() {
+    let entry = nonce_entry();
+    assert!(entry.read_only());
+    assert!(!entry.deterministic);
+    assert!(!entry.cacheable());
+    assert!(echo_entry().deterministic);
+}
+
+#[tokio::test]
+async fn parses_arguments_and_applies_serde_defaults() {
+    let entry = echo_entry();
+    let result = call(
+        &entry,
+        ToolContext::default(),
+        json!({"text": "ab", "times": 2}),
+    )
+    .await;
+    assert_eq!(result.is_error, Some(false));
+    assert_eq!(result_text(&result), "abab");
+
+    let result = call(&entry, ToolContext::default(), json!({"text": "ab"})).await;
+    assert_eq!(result_text(&result), "ab");
+}
+
+#[tokio::test]
+async fn invalid_arguments_become_error_result() {
+    let result = call(&echo_entry(), ToolContext::default(), json!({"times": 2})).await;
+    assert_eq!(result.is_error, Some(true));
+    assert_eq!(
+        result.structured_content.as_ref().unwrap()["error"],
+        "invalid_arguments"
+    );
+}
+
+#[tokio::test]
+async fn tool_error_becomes_error_result() {
+    let entry = divide_entry();
+    let result = call(&entry, ToolContext::default(), json!({"a": 7, "b": 2})).await;
+    assert_eq!(result_text(&result), "3");
+
+    let result = call(&entry, ToolContext::default(), json!({"a": 7, "b": 0})).await;
+    assert_eq!(result.is_error, Some(true));
+    let error = result.structured_content.unwrap();
+    assert_eq!(error["error"], "invalid_arguments");
+    assert_eq!(error["message"], "参数错误: 除数不能为 0");
+}
+
+#[tokio::test]
+async fn serves_generated_tools_over_http() {
+    let router = McpServerBuilder::empty()
+        .tool(echo_entry())
+        .tool(hello_entry())
+        .tool(count_entry())
+        .build_router(serde_yaml::from_str("listen_port: 3000").unwrap())
+        .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await });
+
+    let client = reqwest::Client::new();
+    let post = |body: Value, session: Option<String>| {
+        let mut request = client.post(&url).json(&body);
+        if let Some(session) = session {
+            request = request.header("Mcp-Session-Id", session);
+        }
+        request.send()
+    };
+
+    let response = post(
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+        None,
+    )
+    .await
+    .unwrap();
+    let session = response.headers()["mcp-session-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let list: Value = post(
+        json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+        Some(session.clone()),
+    )
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    let names: Vec<&str> = list["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["echo", "hello", "count"]);
+
+    // 同一会话的调用共享 ToolContext 中的状态
+    for expected in ["1", "2"] {
+        let reply: Value = post(
+            json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                   "params": {"name": "count"}}),
+            Some(session.clone()),
+        )
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+        assert_eq!(reply["result"]["content"][0]["text"], expected);
+    }
+
+    let reply: Value = post(
+        json!({"jsonrpc": "2.0", "id": 4, "method": "tools/call",
+               "params": {"name": "echo", "arguments": {"text": "hi"}}}),
+        None,
+    )
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    assert_eq!(reply["result"]["content"][0]["text"], "hi");
+
+    // 客户端结束会话后，该会话ID不再有效
+    let deleted = client
+        .delete(&url)
+        .header("Mcp-Session-Id", &session)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(deleted.status(), reqwest::StatusCode::NO_CONTENT);
+    let response = post(
+        json!({"jsonrpc": "2.0", "id": 5, "method": "tools/list"}),
+        Some(session),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+}
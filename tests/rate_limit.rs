@@ -0,0 +1,83 @@
+use local_mcp_server::{McpServerBuilder, server::peer::PeerAddr};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+
+/// 在随机端口上运行服务，请求携带对端地址
+async fn serve(config: &str) -> String {
+    let router = McpServerBuilder::new()
+        .only_tools(&["get_system_type"])
+        .build_router(serde_yaml::from_str(config).unwrap())
+        .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<PeerAddr>(),
+        )
+        .await
+    });
+    url
+}
+
+async fn post(url: &str, body: Value, session: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new().post(url).json(&body);
+    if let Some(session) = session {
+        request = request.header("Mcp-Session-Id", session);
+    }
+    request.send().await.unwrap()
+}
+
+async fn initialize(url: &str) -> reqwest::Response {
+    post(
+        url,
+        json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+        None,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn new_sessions_do_not_reset_tool_limits() {
+    let url = serve("rate_limit:\n  per_minute: 1\n  burst: 2\nsession_rate_limit: null").await;
+
+    let mut errors = Vec::new();
+    for _ in 0..3 {
+        let response = initialize(&url).await;
+        let session = response.headers()["mcp-session-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let reply: Value = post(
+            &url,
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                   "params": {"name": "get_system_type"}}),
+            Some(&session),
+        )
+        .await
+        .json()
+        .await
+        .unwrap();
+        errors.push(reply["error"]["code"].clone());
+    }
+    assert_eq!(errors, [Value::Null, Value::Null, json!(-32029)]);
+}
+
+#[tokio::test]
+async fn session_creation_is_rate_limited() {
+    let url = serve("session_rate_limit:\n  per_minute: 1\n  burst: 2").await;
+
+    for _ in 0..2 {
+        let response = initialize(&url).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key("mcp-session-id"));
+    }
+
+    let response = initialize(&url).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(!response.headers().contains_key("mcp-session-id"));
+    let reply: Value = response.json().await.unwrap();
+    assert_eq!(reply["id"], 1);
+    assert_eq!(reply["error"]["code"], -32029);
+    assert!(reply["error"]["data"]["retry_after_ms"].as_u64().unwrap() > 0);
+}